@@ -1,609 +1,9149 @@
-use std::collections::HashMap;
-use rand::Rng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+/// Integer type of the coordinates and dimensions of a minefield. Enabling the `u32-coords` feature allows fields
+/// larger than 65535 spots in either dimension
+#[cfg(not(feature = "u32-coords"))]
+pub type Coord = u16;
+
+/// Integer type of the coordinates and dimensions of a minefield. Enabling the `u32-coords` feature allows fields
+/// larger than 65535 spots in either dimension
+#[cfg(feature = "u32-coords")]
+pub type Coord = u32;
+
+/// Identifier of a player revealing spots with `Minefield::step_as`, chosen by the user of the minefield
+pub type PlayerId = u32;
 
 /// The characteristics of the minefield
 #[derive(Clone, Debug)]
-pub struct Minefield {
-    /// The mine field as a set of coords `(x, y)` associated with a `Spot`
-    field: HashMap<(u16, u16), Spot>,
+pub struct Minefield<T = ()> {
+    /// The mine field as a flat, row-major buffer of packed spots. The spot at coords `(x, y)` is found at index
+    /// `y * width + x`
+    field: Vec<PackedSpot>,
+
+    /// Data attached to each spot by the user of the minefield, at the same indices as `field`
+    data: Vec<T>,
 
     /// Number of mines in the field
     mines: u32,
 
     /// Width of field grid
-    width: u16,
+    width: Coord,
 
     /// Height of field grid
-    height: u16,
+    height: Coord,
+
+    /// Actions applied to the minefield, if history recording was enabled
+    history: Option<History>,
+
+    /// Whether a mine under the very first step is moved away, instead of exploding
+    move_first_mine: bool,
+
+    /// Whether any spot has been stepped on yet
+    started: bool,
+
+    /// Number of flags placed in the field
+    flags: u32,
+
+    /// Number of flags placed on empty spots
+    wrong_flags: u32,
+
+    /// Whether no more flags than mines can be placed
+    limit_flags: bool,
+
+    /// Coordinates of the most recent mine explosion
+    exploded_at: Option<(Coord, Coord)>,
+
+    /// Field indices of the spots which never receive a randomly placed mine
+    reserved: HashSet<usize>,
+
+    /// Whether stepping on an empty spot with no neighboring mines flood reveals the spots around it
+    flood_enabled: bool,
+
+    /// Whether flood reveals clear flagged empty spots with no neighboring mines, and continue through them
+    flood_through_flags: bool,
+
+    /// The generator rearranging the mines as each step happens, if mines are placed adversarially
+    #[cfg(feature = "adversarial")]
+    adversary: Option<Adversary>,
+
+    /// Number of playable spots in the field, i.e. not holes
+    cells: usize,
+
+    /// Number of revealed empty spots in the field
+    revealed: u32,
+
+    /// Number of revealed spots in the field, mines included
+    shown: u32,
+
+    /// Number of actions which changed the minefield
+    moves: u32,
+
+    /// Which marks toggling a flag cycles through
+    flag_mode: FlagMode,
+
+    /// Listener told about the effects of every player action
+    listener: Listener,
+
+    /// Whether player actions are rejected, leaving the minefield as it is
+    locked: bool,
+
+    /// Whether the minefield locks itself once the game is won or lost
+    lock_on_game_over: bool,
+
+    /// Field indices of the spots whose state changed since the last call to `take_dirty`, once it was called
+    dirty: Option<HashSet<usize>>,
+
+    /// The player who revealed each spot, at the same indices as `field`, once any player stepped with `step_as`
+    owners: Vec<Option<PlayerId>>,
+
+    /// Whether the remaining empty spots are revealed as soon as exactly the mines are flagged
+    auto_finish: bool,
+
+    /// Whether the edges of the field grid wrap around, so that spots on opposite edges are neighbors
+    wrapping: bool,
+
+    /// Time spent playing, if timing was enabled
+    timer: Option<Timer>,
 }
 
 impl Minefield {
-    /// Create an empty minefield grid (with all spots hidden), with the given width and height
-    pub fn new(width: u16, height: u16) -> Self {
+    /// Maximum number of coordinates in the field grid of a minefield made by `try_new`
+    pub const MAX_CELLS: usize = 1 << 26;
+
+    /// Start building a minefield with a `MinefieldBuilder`, which is the recommended way to set up a game
+    pub fn builder() -> MinefieldBuilder {
+        MinefieldBuilder::default()
+    }
+
+    /// Create an empty minefield grid (with all spots hidden), with the given width and height.
+    ///
+    /// **A zero width or height is silently replaced by 1**, and the size is not limited. Use `try_new` to reject
+    /// empty and oversized minefields instead
+    pub fn new(width: Coord, height: Coord) -> Self {
         // Enforce a minimum number of spots
         let width = if width == 0 { 1 } else { width };
         let height = if height == 0 { 1 } else { height };
-    
-        let field: HashMap<(u16, u16), Spot> =  
-            (0..width)
-            .flat_map(move |i| {
-                (0..height).map(move |j| (i, j))
-            })
-            .map(|(x, y)| {
-                ((x, y), Spot::default())
-            })
-            .collect();
-            
+
+        let field = vec![PackedSpot::from(Spot::default()); width as usize * height as usize];
+
         // Create empty Minefield
         Minefield {
+            data: vec![(); field.len()],
             field,
             mines: 0,
             width,
             height,
+            history: None,
+            move_first_mine: false,
+            started: false,
+            flags: 0,
+            wrong_flags: 0,
+            limit_flags: false,
+            exploded_at: None,
+            reserved: HashSet::new(),
+            flood_enabled: true,
+            flood_through_flags: false,
+            #[cfg(feature = "adversarial")]
+            adversary: None,
+            cells: width as usize * height as usize,
+            revealed: 0,
+            shown: 0,
+            moves: 0,
+            flag_mode: FlagMode::Flags,
+            listener: Listener(None),
+            locked: false,
+            lock_on_game_over: false,
+            dirty: None,
+            owners: Vec::new(),
+            auto_finish: false,
+            wrapping: false,
+            timer: None,
         }
     }
 
-    /// Build an existing minefield with the given number of mines randomly placed in it
-    pub fn with_mines(mut self, mines: u32) -> Self {
-        // Total number of spots in our field
-        let spot_count = self.width as usize * self.height as usize;
+    /// Create an empty minefield grid (with all spots hidden), with the given width and height, if neither is zero and
+    /// the field grid has at most `MAX_CELLS` coordinates
+    pub fn try_new(width: Coord, height: Coord) -> Result<Self, SizeError> {
+        if width == 0 || height == 0 {
+            return Err(SizeError::Empty);
+        }
 
-        // Limit the max number of mines to the number of available spots
-        let mines = if mines as usize <= spot_count { mines } else { spot_count as u32 };
+        match (width as usize).checked_mul(height as usize) {
+            Some(cells) if cells <= Self::MAX_CELLS => Ok(Minefield::new(width, height)),
+            _ => Err(SizeError::TooLarge { width, height }),
+        }
+    }
 
-        self.mines = mines;
+    /// Create an empty minefield grid with the given bounding width and height, where only the coordinates for which
+    /// `mask` returns `true` hold a spot. Masked-out coordinates are holes: they have no spot, they are nobody's
+    /// neighbor, and they never receive mines
+    pub fn with_shape(width: Coord, height: Coord, mask: impl Fn(Coord, Coord) -> bool) -> Self {
+        let mut minefield = Minefield::new(width, height);
 
-        // Add mines to minefield
+        for index in 0..minefield.field.len() {
+            let (x, y) = minefield.coords(index);
 
-        // We could just start randomly picking indices in the field and hope we haven't picked them before, but if a
-        // user desires a field full of mines, then waiting for the last mines to be placed might take a long time
-        // (e.g. if the field is very large).
-        // That's a problem for an immediate GUI.
-        // So, instead, we'll use some memory in order to ensure that the user can step on a mine as soon as humanly
-        // possible.
-        let mut spots_remaining: Vec<usize> = (0..spot_count).collect();
-        let mut rng = rand::thread_rng();
+            if !mask(x, y) {
+                minefield.field[index] = PackedSpot::HOLE;
+                minefield.cells -= 1;
+            }
+        }
 
-        // Place mines
-        for _ in 0..self.mines {
-            let index_rm = rng.gen_range(0..spots_remaining.len());
+        minefield
+    }
+
+    /// Create the daily challenge minefield for the given key (e.g. a date like `"2024-06-01"`), which is the same on
+    /// every platform and every release of this crate. The key is hashed with 64 bit FNV-1a into the seed of a
+    /// SplitMix64 generator, and each mine is placed on the spot at index `next_u64() % remaining` of the spots still
+    /// without a mine. Neither the hash, the generator nor the placement may ever change
+    pub fn daily(width: Coord, height: Coord, mines: u32, key: &str) -> Self {
+        let mut minefield = Minefield::new(width, height);
+        let mut rng = SplitMix64(fnv1a(key.bytes()));
+
+        let mut spots_remaining: Vec<usize> = minefield.indices().collect();
+        let mines = mines.min(spots_remaining.len() as u32);
+
+        for _ in 0..mines {
+            let index_rm = (rng.next_u64() % spots_remaining.len() as u64) as usize;
             let index = spots_remaining.swap_remove(index_rm);
-            let x = (index as u32 % self.width as u32) as u16;
-            let y = (index as u32 / self.width as u32) as u16;
-            self.place_mine(x, y);
+            let (x, y) = minefield.coords(index);
+            minefield.place_mine(x, y).ok();
         }
 
-        self
+        minefield
     }
 
-    /// Step on a given spot of the field. Coordinates [x=0, y=0] represent the top-left point of the field grid
-    pub fn step(&mut self, x: u16, y: u16) -> StepResult {
-        if let Some(spot) = self.field.get_mut(&(x, y)) {
-            let step_result = spot.step();
-
-            // flood reveal, if this is an empty spot with no neighboring mines
-            if let SpotState::RevealedEmpty { neighboring_mines: 0 } = spot.state {
-                let mut spots_to_visit = vec![(x, y)];
-
-                while let Some((xx, yy)) = spots_to_visit.pop() {                            
-                    for n_coords in self.neighbors_coords(xx, yy) {
-                        let spot = self.field.get_mut(&n_coords).unwrap();
-                        
-                        if let SpotState::HiddenEmpty { neighboring_mines } = spot.state {
-                            // Reveal the hidden empty spot by stepping on it
-                            let _step_result = spot.step();
-                            assert_eq!(_step_result, StepResult::Phew);
-
-                            if neighboring_mines == 0 {
-                                // contine flood revealing neighbors from this spot
-                                spots_to_visit.push(n_coords);
-                            }
-                        }
-                    }
-                }
-            }
+    /// Generate seeded minefields until one has a 3BV (see `bbbv`) within the given range, giving up after
+    /// `max_attempts`. The seed of each attempt is derived from the given seed with a SplitMix64 generator, so the same
+    /// arguments always produce the same minefield, which can also be rebuilt with `with_seeded_mines` and the
+    /// returned seed
+    pub fn generate_with_bbbv(
+        width: Coord,
+        height: Coord,
+        mines: u32,
+        range: RangeInclusive<u32>,
+        seed: u64,
+        max_attempts: u32,
+    ) -> Result<Generated, GenerationError> {
+        let mut seeds = SplitMix64(seed);
+        let mut minefield = Minefield::new(width, height);
 
-            step_result
-        } else {
-            // Step is outside minefield
-            StepResult::Invalid
+        for attempt in 1..=max_attempts {
+            let attempt_seed = seeds.next_u64();
+            minefield.regenerate(mines, attempt_seed);
+
+            if u32::try_from(minefield.bbbv()).is_ok_and(|bbbv| range.contains(&bbbv)) {
+                return Ok(Generated { minefield, attempts: attempt, seed: attempt_seed });
+            }
         }
+
+        Err(GenerationError::NoMatch { attempts: max_attempts })
     }
 
-    /// Automatically step on all hidden neighbors (i.e. not flagged) of a revealed spot at the given coordiantes
-    pub fn auto_step(&mut self, x: u16, y: u16) -> StepResult {
-        if let Some(spot) = self.field.get(&(x, y)) {
-            if let SpotState::RevealedEmpty { neighboring_mines } = spot.state {
-                 // count the flags around the given coords
-                 let placed_flags = self
-                    .neighbors_coords(x, y)
-                    .filter(|(x, y)| {
-                        matches!(
-                            self.field.get(&(*x, *y)).unwrap().state, 
-                            SpotState::FlaggedEmpty { neighboring_mines: _ } | SpotState::FlaggedMine
-                        )
-                    })
-                    .count() as u8;
-                            
-                // Only try to autostep if the user has placed enough flags around the spot whose neighbors will be 
-                // autorevealed
-                if placed_flags == neighboring_mines {
-                    for (nx, ny) in self.neighbors_coords(x, y) {
-                        if StepResult::Boom == self.step(nx, ny) {
-                            // Eager Boom return
-                            return StepResult::Boom;
-                        }
-                    }
+    /// Create a minefield grid with the given width and height, with mines where `layout` is `true`, in row-major order
+    /// (see `mine_layout`). Missing values of a short layout are treated as `false`, and extra values are ignored
+    pub fn from_mine_layout(width: Coord, height: Coord, layout: &[bool]) -> Self {
+        let mut minefield = Minefield::new(width, height);
 
-                    StepResult::Phew
-                } else {
-                    // Not enough flags placed by user in order to autostep
-                    StepResult::Invalid
-                }
-            } else {
-                // Spot is not revealed yet
-                StepResult::Invalid
+        for (index, &mine) in layout.iter().take(minefield.field.len()).enumerate() {
+            if mine {
+                minefield.update(index, |spot| spot.state = SpotState::HiddenMine);
+                minefield.mines += 1;
             }
-        } else {
-            // invalid spot coordinates
-            StepResult::Invalid
         }
+
+        minefield.recompute_counts();
+        minefield
     }
 
-    /// Check if the minefield has been cleared
-    pub fn is_cleared(&self) -> bool {
-        for (_spot_coords, spot) in self.spots() {
-            if !spot.is_resolved() {
-                return false;
+    /// Create a minefield grid with the given width and height, where `f` decides which coordinates hold a mine
+    pub fn from_fn(width: Coord, height: Coord, f: impl Fn(Coord, Coord) -> bool) -> Self {
+        let mut minefield = Minefield::new(width, height);
+
+        for index in 0..minefield.field.len() {
+            let (x, y) = minefield.coords(index);
+
+            if f(x, y) {
+                minefield.place_mine(x, y).unwrap();
             }
         }
 
-        true
+        minefield
     }
 
-    /// Set a flag on a hidden spot, or clear the flag if the spot had one, or do nothing if
-    /// the spot cannot be flagged
-    pub fn toggle_flag(&mut self, x: u16, y: u16) -> FlagToggleResult {
-        if let Some(spot) = self.field.get_mut(&(x, y)) {
-            spot.flag()
-        } else {
-            // invalid coordinates, no flag was added or removed
-            FlagToggleResult::None
+    /// Decode a minefield encoded with `to_bytes`, rejecting malformed or inconsistent data. The order of the
+    /// explosions isn't encoded, so should several mines have exploded, the first one in row-major order is taken as
+    /// the most recent (see `exploded_at`)
+    pub fn from_bytes(data: &[u8]) -> Result<Minefield, DecodeError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(DecodeError::Length { expected: Self::HEADER_LEN, actual: data.len() });
+        }
+
+        if data[0..4] != Self::MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+
+        if data[4] != Self::FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version: data[4] });
+        }
+
+        let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let width = Coord::try_from(read_u32(5)).map_err(|_| DecodeError::TooLarge)?;
+        let height = Coord::try_from(read_u32(9)).map_err(|_| DecodeError::TooLarge)?;
+        let mines = read_u32(13);
+
+        // Minefields have at least one spot in each direction
+        let expected = (width.max(1) as usize)
+            .checked_mul(height.max(1) as usize)
+            .and_then(|cells| cells.checked_add(Self::HEADER_LEN))
+            .ok_or(DecodeError::TooLarge)?;
+
+        if data.len() != expected {
+            return Err(DecodeError::Length { expected, actual: data.len() });
+        }
+
+        let mut minefield = Minefield::new(width, height);
+
+        for (index, &byte) in data[Self::HEADER_LEN..].iter().enumerate() {
+            let packed = PackedSpot(byte);
+
+            if packed != PackedSpot::HOLE && !packed.is_valid() {
+                let (x, y) = minefield.coords(index);
+                return Err(DecodeError::InvalidSpot { x, y });
+            }
+
+            minefield.field[index] = packed;
+        }
+
+        // Restore the counters from the decoded spots
+        let cells = minefield.indices().count();
+        let revealed = minefield.spots().filter(|(_coords, spot)| spot.is_revealed() && !spot.is_mine()).count() as u32;
+        let shown = minefield.spots().filter(|(_coords, spot)| spot.is_revealed()).count() as u32;
+        let flags = minefield.spots().filter(|(_coords, spot)| spot.is_flagged()).count() as u32;
+        let wrong_flags = minefield
+            .spots()
+            .filter(|(_coords, spot)| matches!(spot.state, SpotState::FlaggedEmpty { neighboring_mines: _ }))
+            .count() as u32;
+        let started = minefield.spots().any(|(_coords, spot)| spot.is_revealed());
+        let exploded_at = minefield.spots().find(|(_coords, spot)| spot.is_exploded()).map(|(coords, _spot)| coords);
+        minefield.cells = cells;
+        minefield.revealed = revealed;
+        minefield.shown = shown;
+        minefield.mines = mines;
+        minefield.flags = flags;
+        minefield.wrong_flags = wrong_flags;
+        minefield.started = started;
+        minefield.exploded_at = exploded_at;
+
+        if let Err(errors) = minefield.validate() {
+            return Err(DecodeError::Inconsistent { error: errors[0] });
         }
+
+        Ok(minefield)
     }
+}
 
-    /// The width of the minefield
-    pub fn width(&self) -> u16 {
-        self.width
+impl<T> Minefield<T> {
+    /// Magic bytes at the start of the binary format
+    const MAGIC: [u8; 4] = *b"MFLD";
+
+    /// Version of the binary format
+    const FORMAT_VERSION: u8 = 1;
+
+    /// Length of the header of the binary format: magic, version, width, height and mines
+    const HEADER_LEN: usize = 17;
+
+    /// Maximum number of hidden spots next to revealed spots for which `is_consistent` searches a placement of mines
+    pub const CONSISTENCY_FRONTIER_LIMIT: usize = 24;
+
+    /// Build an existing minefield with the given number of mines randomly placed in it. Mines already in the
+    /// minefield (and not revealed) are removed first, so calling this again re-rolls the mines. Asking for more mines
+    /// than there are spots which may receive one silently places fewer; use `try_with_mines` to catch that
+    pub fn with_mines(mut self, mines: u32) -> Self {
+        self.clear_mines();
+        self.place_random_mines(mines, &mut rand::thread_rng());
+        self
     }
 
-    /// The height of the minefield
-    pub fn height(&self) -> u16 {
-        self.height
+    /// Like `with_mines`, but asking for more mines than there are spots which may receive one (not holes, nor
+    /// reserved) is a `PlacementError::TooManyMines` instead of a minefield with fewer mines
+    pub fn try_with_mines(self, mines: u32) -> Result<Self, PlacementError> {
+        let max = (self.cells - self.reserved.len()) as u32;
+
+        if mines > max {
+            return Err(PlacementError::TooManyMines { mines, max });
+        }
+
+        Ok(self.with_mines(mines))
     }
 
-    /// The number of mines in the minefield
-    pub fn mines(&self) -> u32 {
-        self.mines
-    }    
+    /// Build an existing minefield with the given number of mines randomly placed in it, where the same seed always
+    /// produces the same placement of mines. Like `with_mines`, this replaces the mines already in the minefield
+    pub fn with_seeded_mines(mut self, mines: u32, seed: u64) -> Self {
+        self.clear_mines();
+        self.place_random_mines(mines, &mut StdRng::seed_from_u64(seed));
+        self
+    }
 
-    /// Get a reference to a particular `Spot` in the field
-    pub fn spot(&self, x: u16, y: u16) -> Option<&Spot> {
-        self.field.get(&(x, y))
+    /// Build an existing minefield with mines randomly placed in symmetric pairs, like `with_symmetric_mines`, but
+    /// without a seed
+    pub fn with_mines_symmetric(mut self, mines: u32, symmetry: Symmetry) -> Self {
+        self.clear_mines();
+        self.place_symmetric_mines(mines, symmetry, &mut rand::thread_rng());
+        self
     }
 
-    /// Iterator for all `Spot`s in the field, together with their coordinates `(x, y)`
-    pub fn spots(&self) -> impl Iterator<Item = (&(u16, u16), &Spot)> {
-        self.field.iter()
+    /// Build an existing minefield with mines randomly placed in symmetric pairs, each mine together with its mirror
+    /// image under the given symmetry, where the same seed always produces the same placement of mines.
+    ///
+    /// Spots which are their own mirror image (e.g. the center of a field with odd dimensions) hold a single mine, and
+    /// are used to reach an odd number of mines. When the requested number of mines can't be reached exactly, the field
+    /// gets as many mines as possible without breaking the symmetry. Like `with_mines`, this replaces the mines
+    /// already in the minefield
+    pub fn with_symmetric_mines(mut self, mines: u32, axis: Symmetry, seed: u64) -> Self {
+        self.clear_mines();
+        self.place_symmetric_mines(mines, axis, &mut StdRng::seed_from_u64(seed));
+        self
     }
 
-    /// Place a mine at a given field coordiantes, and update neighboring spots
-    fn place_mine(&mut self, x: u16, y: u16) {
-        
-        assert!(x < self.width);
-        assert!(y < self.height);
-        
-        if let Some(spot) = self.field.get_mut(&(x, y)) {
-            match spot.state {
-                // Only place a mine in an emty field
-                SpotState::HiddenEmpty { neighboring_mines: _ } | 
-                SpotState::FlaggedEmpty { neighboring_mines: _ } | 
-                SpotState::RevealedEmpty { neighboring_mines: _ } => {
-                    spot.state = SpotState::HiddenMine;
-                    
-                    // Update counts of empty neighboring spots
-                    for (nx, ny) in self.neighbors_coords(x, y) {
-                        if let Some(spot) = self.field.get_mut(&(nx, ny)) {
-                            match &mut spot.state {
-                                // Only place a mine in an emty field
-                                SpotState::HiddenEmpty { neighboring_mines } | 
-                                SpotState::FlaggedEmpty { neighboring_mines } | 
-                                SpotState::RevealedEmpty { neighboring_mines } => {
-                                    *neighboring_mines += 1;
-                                },
-                                _ => {},
-                            }
-                        }
-                    }                    
-                },
-                _ => {},
-            }
+    /// Build an existing minefield with the given number of mines randomly placed in it, such that no new mine is a
+    /// neighbor of any other mine. No more than `ceil(width / 2) * ceil(height / 2)` mines fit a grid this way (one per
+    /// 2x2 block), and asking for more is a `PlacementError::TooManyMines`. Holes, reserved spots and mines already in
+    /// place leave less room, and when too little is left, this is a `PlacementError::Unsatisfiable`
+    pub fn with_mines_spread(mut self, mines: u32) -> Result<Self, PlacementError> {
+        let max = (self.width as u64).div_ceil(2) * (self.height as u64).div_ceil(2);
+        let max = u32::try_from(max).unwrap_or(u32::MAX);
+
+        if mines > max {
+            return Err(PlacementError::TooManyMines { mines, max });
+        }
+
+        let spots = self
+            .spread_mine_spots(mines, &mut rand::thread_rng())
+            .ok_or(PlacementError::Unsatisfiable { mines })?;
+
+        for index in spots {
+            let (x, y) = self.coords(index);
+            self.place_mine(x, y).unwrap();
         }
+
+        Ok(self)
     }
 
-    /// Iterator over the coordinates of all neighbors in a range of 1 unit, relative to the given coordiantes
-    fn neighbors_coords(&self, x: u16, y: u16) -> impl Iterator<Item = (u16, u16)>
-    {        
-        let min_x = x.saturating_sub(1);
-        let max_x = x.saturating_add(1);
+    /// Build an existing minefield with the given number of mines randomly placed in it, outside the rectangle with the
+    /// given opposite corners `(x0, y0, x1, y1)` (both included), e.g. to open a game with `reveal_rect`. The
+    /// rectangle must be within the minefield, and leave room for the mines. Like `with_mines`, this replaces the
+    /// mines already in the minefield
+    pub fn with_mines_excluding_rect(
+        mut self,
+        mines: u32,
+        rect: (Coord, Coord, Coord, Coord),
+    ) -> Result<Self, PlacementError> {
+        let (x0, y0, x1, y1) = rect;
 
-        let min_y = y.saturating_sub(1);
-        let max_y = y.saturating_add(1);
+        if let Some(&(x, y)) = [(x0, y0), (x1, y1)].iter().find(|(x, y)| *x >= self.width || *y >= self.height) {
+            return Err(PlacementError::OutOfBounds { x, y });
+        }
 
-        let width = self.width;
-        let height = self.height;
+        self.clear_mines();
 
-        (min_x..=max_x)
-            .flat_map(move |i| {
-                (min_y..=max_y).map(move |j| (i, j))
-            })
-            .filter(move |(neighbor_x, neighbor_y)| {
-                // the neighbor coords are within the minefield grid
-                *neighbor_x < width && *neighbor_y < height && 
-                // the neighbor coords are not same as `self`
-                !(*neighbor_x == x && *neighbor_y == y)
-            })       
+        let excluded: HashSet<usize> = self
+            .spots_in_rect(x0, y0, x1, y1)
+            .filter_map(|((x, y), _spot)| self.index(x, y))
+            .filter(|index| !self.reserved.contains(index))
+            .collect();
+        let free = self
+            .indices()
+            .filter(|index| !self.reserved.contains(index) && !excluded.contains(index) && !self.get(*index).is_mine())
+            .count();
+
+        if mines as usize > free {
+            return Err(PlacementError::TooManyMines { mines, max: free as u32 });
+        }
+
+        // Reserve the rectangle only while placing the mines
+        self.reserved.extend(&excluded);
+        self.place_random_mines(mines, &mut rand::thread_rng());
+        excluded.iter().for_each(|index| {
+            self.reserved.remove(index);
+        });
+
+        Ok(self)
     }
-}
 
-/// State of the spot in a minefield
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum SpotState {
-    /// This spot has not been visited
-    HiddenEmpty{neighboring_mines: u8},
+    /// Build an existing minefield where the given spots never receive a randomly placed mine, neither from mine
+    /// generation (so this should come before e.g. `with_mines`) nor from moving the first mine. Coordinates outside
+    /// the minefield are ignored
+    pub fn with_reserved_safe(mut self, cells: impl IntoIterator<Item = (Coord, Coord)>) -> Self {
+        let reserved: Vec<usize> = cells.into_iter().filter_map(|(x, y)| self.index(x, y)).collect();
+        self.reserved.extend(reserved);
+        self
+    }
 
-    /// This is a hidden mine
-    HiddenMine,
+    /// Build an existing minefield where stepping on a mine with the very first step doesn't explode it. Instead, the
+    /// mine is moved to the first spot without a mine (in row-major order), and the stepped spot is revealed
+    pub fn with_move_first_mine(mut self, move_first_mine: bool) -> Self {
+        self.move_first_mine = move_first_mine;
+        self
+    }
 
-    /// This spot is empty, but was flagged as a mine
-    FlaggedEmpty{neighboring_mines: u8},
+    /// Build an existing minefield where stepping on an empty spot with no neighboring mines reveals only that spot,
+    /// instead of flood revealing the spots around it. Flooding is enabled by default, and `flood_from` always floods
+    pub fn with_flood(mut self, flood_enabled: bool) -> Self {
+        self.flood_enabled = flood_enabled;
+        self
+    }
 
-    /// This spot contains a mine which was correctly flagged
-    FlaggedMine,
+    /// Build an existing minefield where flood reveals remove the flags from empty spots with no neighboring mines,
+    /// reveal them, and continue flooding through them. By default, flood reveals stop at flags
+    pub fn with_flood_through_flags(mut self, flood_through_flags: bool) -> Self {
+        self.flood_through_flags = flood_through_flags;
+        self
+    }
 
-    /// This spot is empty and was correctly revealed
-    RevealedEmpty{neighboring_mines: u8},
+    /// Build an existing minefield which decides where its mines are as the player steps, in the style of "Kaboom". The
+    /// mines are rearranged before each step, keeping the layout consistent with everything revealed so far:
+    /// - stepping on a provably safe spot reveals it
+    /// - guessing while a provably safe spot is left (as far as `deduce()` can tell) steps on a mine, if any
+    ///   consistent layout puts one there
+    /// - a guess which can't be avoided is safe, if any consistent layout allows it
+    ///
+    /// The layouts are searched with a bounded effort, and the current layout is kept when that runs out, so very
+    /// large unresolved frontiers fall back to fixed mines. This is only available with the `adversarial` feature
+    #[cfg(feature = "adversarial")]
+    pub fn with_adversarial(self, adversarial: bool) -> Self {
+        if adversarial {
+            self.with_seeded_adversarial(rand::thread_rng().gen())
+        } else {
+            Minefield { adversary: None, ..self }
+        }
+    }
 
-    /// This spot contains a mine and was incorrectly revealed
-    ExplodedMine,
-}
+    /// Like `with_adversarial`, with the mines rearranged by a generator seeded with the given seed, so that the same
+    /// actions on the same minefield always play out the same way. `BoardSpec` keeps the seed for replays
+    #[cfg(feature = "adversarial")]
+    pub fn with_seeded_adversarial(mut self, seed: u64) -> Self {
+        self.adversary = Some(Adversary { seed, rng: StdRng::seed_from_u64(seed) });
+        self
+    }
 
-/// Spot struct describing the characteristics of the minefield at a particular position
-#[derive(Copy, Clone, Debug)]
-pub struct Spot {
-    pub state: SpotState,
-}
+    /// Build an existing minefield where no more flags than mines can be placed. Once the limit is reached, adding a
+    /// flag results in `FlagToggleResult::LimitReached`, while removing flags still works
+    pub fn limit_flags_to_mines(mut self, limit_flags: bool) -> Self {
+        self.limit_flags = limit_flags;
+        self
+    }
 
-impl Spot {
-    /// Step on this spot, if possible
-    fn step(&mut self) -> StepResult {
-        match self.state {
-            SpotState::HiddenEmpty { neighboring_mines } => {
-                self.state = SpotState::RevealedEmpty { neighboring_mines };
-                StepResult::Phew
-            },
-            SpotState::HiddenMine => {
-                self.state = SpotState::ExplodedMine;
-                StepResult::Boom
-            },
-            _ => {
-                StepResult::Invalid
-            }
+    /// Build an existing minefield where, as soon as a flag toggle leaves every mine flagged and no empty spot flagged,
+    /// all the remaining hidden empty spots are stepped on as part of the same action, which wins the game
+    pub fn auto_finish_on_full_flag(mut self, auto_finish: bool) -> Self {
+        self.auto_finish = auto_finish;
+        self
+    }
+
+    /// Build an existing minefield whose edges wrap around like a torus: spots on the left edge neighbor the spots on
+    /// the right edge, and spots on the top edge neighbor the spots on the bottom edge. The neighboring mine counts of
+    /// the mines already in the minefield are updated
+    pub fn with_wrapping(mut self, wrapping: bool) -> Self {
+        self.wrapping = wrapping;
+
+        for index in self.indices().collect::<Vec<_>>() {
+            let (x, y) = self.coords(index);
+            let count = self.neighboring_mines(x, y);
+
+            self.update(index, |spot| {
+                if let SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::FlaggedEmpty { neighboring_mines } |
+                    SpotState::RevealedEmpty { neighboring_mines } |
+                    SpotState::WrongFlag { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } = &mut spot.state {
+                    *neighboring_mines = count;
+                }
+            });
         }
+
+        self
     }
 
-    /// Toggle a flag this spot, if possible
-    fn flag(&mut self) -> FlagToggleResult {
-        match self.state {
-            SpotState::HiddenEmpty { neighboring_mines } => {
-                self.state = SpotState::FlaggedEmpty { neighboring_mines };
-                FlagToggleResult::Added
-            },
-            SpotState::HiddenMine => {
-                self.state = SpotState::FlaggedMine {};
-                FlagToggleResult::Added
-            },
-            SpotState::FlaggedEmpty { neighboring_mines } => {
-                self.state = SpotState::HiddenEmpty { neighboring_mines };
-                FlagToggleResult::Removed
-            },
-            SpotState::FlaggedMine => {
-                self.state = SpotState::HiddenMine {};
-                FlagToggleResult::Removed
-            },
-            _ => {
-                FlagToggleResult::None
-            }
+    /// Build an existing minefield which times the game with the real clock (see `with_clock`), or stops timing it
+    pub fn with_timer(self, timer: bool) -> Self {
+        if timer {
+            self.with_clock(SystemClock::new())
+        } else {
+            Minefield { timer: None, ..self }
         }
     }
 
-    /// Has this spot been cleared (either correctly flagged or correctly revealed)?
-    fn is_resolved(&self) -> bool {
-        matches!(
-            self.state, 
-            SpotState::FlaggedMine | SpotState::RevealedEmpty { neighboring_mines: _ }
-        )
+    /// Build an existing minefield which times the game with the given clock: the timer starts with the first step
+    /// which reveals a spot, stops once the game is won or lost, and doesn't run while paused (see `pause`)
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.timer = Some(Timer::new(Arc::new(clock)));
+        self.update_timer();
+        self
     }
-}
 
-impl Default for Spot {
-    fn default() -> Self {
-        Self { state: SpotState::HiddenEmpty { neighboring_mines: 0 } }
+    /// Build an existing minefield which locks itself (see `lock`) as soon as a player action wins or loses the game,
+    /// so that stray input can't change the final board
+    pub fn with_lock_on_game_over(mut self, lock_on_game_over: bool) -> Self {
+        self.lock_on_game_over = lock_on_game_over;
+        self
     }
-}
 
-/// The result of steppin on a spot in the minefield
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum StepResult {
-    /// Stepped on empty spot
-    Phew,
+    /// Convert into a minefield which attaches data of type `U` to each spot, starting from `U::default()`. Any
+    /// previously attached data is dropped
+    pub fn with_cell_data<U: Default>(self) -> Minefield<U> {
+        let mut data = Vec::with_capacity(self.field.len());
+        data.resize_with(self.field.len(), U::default);
+
+        Minefield {
+            field: self.field,
+            data,
+            mines: self.mines,
+            width: self.width,
+            height: self.height,
+            history: self.history,
+            move_first_mine: self.move_first_mine,
+            started: self.started,
+            flags: self.flags,
+            wrong_flags: self.wrong_flags,
+            limit_flags: self.limit_flags,
+            exploded_at: self.exploded_at,
+            reserved: self.reserved,
+            flood_enabled: self.flood_enabled,
+            flood_through_flags: self.flood_through_flags,
+            #[cfg(feature = "adversarial")]
+            adversary: self.adversary,
+            cells: self.cells,
+            revealed: self.revealed,
+            shown: self.shown,
+            moves: self.moves,
+            flag_mode: self.flag_mode,
+            listener: self.listener,
+            locked: self.locked,
+            lock_on_game_over: self.lock_on_game_over,
+            dirty: self.dirty,
+            owners: self.owners,
+            auto_finish: self.auto_finish,
+            wrapping: self.wrapping,
+            timer: self.timer,
+        }
+    }
+
+    /// Start a new game on the same minefield, reusing its allocations: every spot becomes hidden and empty again, and
+    /// the given number of mines is placed like `with_seeded_mines` does. Holes, reserved spots, options and attached
+    /// data are kept, while the game progress (and the history, if recording) starts over
+    pub fn regenerate(&mut self, mines: u32, seed: u64) {
+        let hidden = PackedSpot::from(Spot::default());
+
+        for (index, packed) in self.field.iter_mut().enumerate() {
+            if *packed != PackedSpot::HOLE && *packed != hidden {
+                *packed = hidden;
+
+                if let Some(dirty) = &mut self.dirty {
+                    dirty.insert(index);
+                }
+            }
+        }
+
+        self.mines = 0;
+        self.flags = 0;
+        self.wrong_flags = 0;
+        self.revealed = 0;
+        self.shown = 0;
+        self.moves = 0;
+        self.started = false;
+        self.exploded_at = None;
+        self.locked = false;
+        self.owners.clear();
+
+        if let Some(timer) = &mut self.timer {
+            *timer = Timer::new(timer.clock.clone());
+        }
+
+        if let Some(history) = &mut self.history {
+            history.start = Instant::now();
+            history.actions.clear();
+        }
+
+        self.place_random_mines(mines, &mut StdRng::seed_from_u64(seed));
+    }
+
+    /// The minefield as seen by the player, one line of glyphs (see `Spot::glyph`) per row, with `#` for holes
+    pub fn render(&self) -> String {
+        self.render_charset(&RenderCharset::ASCII)
+    }
+
+    /// Like `render`, with the characters of the given character set, e.g. `RenderCharset::UNICODE`
+    pub fn render_charset(&self, charset: &RenderCharset) -> String {
+        self.render_with(charset.hole, |spot| spot.glyph_in(charset).to_string())
+    }
+
+    /// Like `render`, with the glyphs colored by ANSI escape codes for terminals: counts in the classic minesweeper
+    /// colors, flags in red, and mines in bold
+    #[cfg(feature = "ansi")]
+    pub fn render_ansi(&self) -> String {
+        self.render_with(RenderCharset::ASCII.hole, |spot| match spot.ansi_color() {
+            Some(color) => format!("{}{}\x1b[0m", color, spot.glyph()),
+            None => spot.glyph().to_string(),
+        })
+    }
+
+    /// Render every row of the minefield on its own line, with the given text for each spot and character for holes
+    fn render_with(&self, hole: char, spot_text: impl Fn(Spot) -> String) -> String {
+        let mut rendered = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                match self.spot(x, y) {
+                    Some(spot) => rendered.push_str(&spot_text(spot)),
+                    None => rendered.push(hole),
+                }
+            }
+
+            rendered.push('\n');
+        }
+
+        rendered
+    }
+
+    /// Choose which marks toggling a flag cycles through. Question marks left over from another mode are cleared by
+    /// the next toggle
+    pub fn set_flag_mode(&mut self, flag_mode: FlagMode) {
+        self.flag_mode = flag_mode;
+    }
+
+    /// Reject every player action until `unlock` is called: steps and chords result in `StepResult::Invalid`, and
+    /// toggling a flag in `FlagToggleResult::None`, without changing the minefield or recording anything
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    /// Accept player actions again after `lock`
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    /// Are player actions currently rejected?
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Time spent playing so far, not counting pauses, as measured by the timer. Zero if timing isn't enabled (see
+    /// `with_clock`)
+    pub fn elapsed(&self) -> Duration {
+        self.timer.as_ref().map_or(Duration::ZERO, Timer::elapsed)
+    }
+
+    /// Pause the timer, e.g. while the game is hidden. Player actions are still accepted, but not timed
+    pub fn pause(&mut self) {
+        if let Some(timer) = &mut self.timer {
+            timer.paused = true;
+        }
+
+        self.update_timer();
+    }
+
+    /// Let the timer run again after `pause`, if the game is in progress
+    pub fn resume(&mut self) {
+        if let Some(timer) = &mut self.timer {
+            timer.paused = false;
+        }
+
+        self.update_timer();
+    }
+
+    /// Is the timer paused?
+    pub fn is_paused(&self) -> bool {
+        self.timer.as_ref().is_some_and(|timer| timer.paused)
+    }
+
+    /// Coordinates `(x, y)` of every spot whose state changed since the previous call, in row-major order, whatever
+    /// changed it (player actions, edits, `finalize_loss`, `regenerate`, `restore`...). The first call returns every
+    /// spot, since nothing was tracked before it
+    pub fn take_dirty(&mut self) -> Vec<(Coord, Coord)> {
+        let mut indices: Vec<usize> = match self.dirty.replace(HashSet::new()) {
+            Some(dirty) => dirty.into_iter().collect(),
+            None => self.indices().collect(),
+        };
+        indices.sort_unstable();
+
+        indices.into_iter().map(|index| self.coords(index)).collect()
+    }
+
+    /// Set the listener which is told about the effects of every player action (see `FieldListener`), replacing any
+    /// previous one. Listeners are not cloned together with the minefield
+    pub fn set_listener(&mut self, listener: Box<dyn FieldListener>) {
+        self.listener = Listener(Some(listener));
+    }
+
+    /// Remove the listener of the minefield, and hand it back
+    pub fn take_listener(&mut self) -> Option<Box<dyn FieldListener>> {
+        self.listener.0.take()
+    }
+
+    /// Start recording every action applied to the minefield, together with the time elapsed since recording started
+    pub fn enable_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(History { start: Instant::now(), actions: Vec::new() });
+        }
+    }
+
+    /// The actions applied to the minefield since history recording was enabled
+    pub fn history(&self) -> &[TimestampedAction] {
+        match &self.history {
+            Some(history) => &history.actions,
+            None => &[],
+        }
+    }
+
+    /// Apply a player action to the minefield, and report its outcome together with the coordinates of every spot
+    /// whose state was changed by it. All player actions go through here, so that replaying recorded actions follows
+    /// exactly the same path as live play. While the minefield is locked, every action is rejected
+    pub fn apply(&mut self, action: Action) -> ActionResult {
+        if self.locked {
+            let outcome = match action {
+                Action::ToggleFlag { .. } => ActionOutcome::Flag(FlagToggleResult::None),
+                _ => ActionOutcome::Step(StepResult::Invalid),
+            };
+
+            return ActionResult { outcome, changed: Vec::new() };
+        }
+
+        let mut changed = Vec::new();
+        let was_cleared = self.safe_spots_remaining() == 0;
+
+        let outcome = match action {
+            Action::Step { x, y } => ActionOutcome::Step(self.step_spot(x, y, &mut changed)),
+            Action::AutoStep { x, y } => ActionOutcome::Step(self.auto_step_spot(x, y, &mut changed)),
+            Action::ToggleFlag { x, y } => ActionOutcome::Flag(self.toggle_flag_spot(x, y, &mut changed)),
+            Action::SmartChord { x, y } => ActionOutcome::Step(self.smart_chord_spot(x, y, &mut changed)),
+        };
+
+        if !changed.is_empty() {
+            self.moves += 1;
+        }
+
+        let cleared = !was_cleared && self.safe_spots_remaining() == 0 && self.exploded_at.is_none();
+
+        if self.lock_on_game_over && self.game_state() != GameState::InProgress {
+            self.locked = true;
+        }
+
+        self.update_timer();
+
+        if let Some(mut listener) = self.listener.0.take() {
+            for &(x, y) in &changed {
+                let spot = self.spot(x, y).unwrap();
+
+                match spot.state {
+                    SpotState::RevealedEmpty { neighboring_mines } => listener.on_reveal((x, y), neighboring_mines),
+                    SpotState::ExplodedMine => listener.on_explode((x, y)),
+                    _ => listener.on_flag((x, y), spot.is_flagged()),
+                }
+            }
+
+            if cleared {
+                listener.on_cleared();
+            }
+
+            self.listener.0 = Some(listener);
+        }
+
+        if let Some(history) = &mut self.history {
+            history.actions.push(TimestampedAction { action, elapsed: history.start.elapsed() });
+        }
+
+        ActionResult { outcome, changed }
+    }
+
+    /// Step on a given spot of the field. Coordinates [x=0, y=0] represent the top-left point of the field grid
+    pub fn step(&mut self, x: Coord, y: Coord) -> StepResult {
+        match self.apply(Action::Step { x, y }).outcome {
+            ActionOutcome::Step(step_result) => step_result,
+            ActionOutcome::Flag(_) => unreachable!(),
+        }
+    }
+
+    /// Step on a given spot of the field on behalf of a player, who then owns every spot the step reveals, including
+    /// the spots flooded from it (see `owner_of`)
+    pub fn step_as(&mut self, player: PlayerId, x: Coord, y: Coord) -> StepResult {
+        let result = self.apply(Action::Step { x, y });
+
+        if !result.changed.is_empty() && self.owners.is_empty() {
+            self.owners = vec![None; self.field.len()];
+        }
+
+        for &(cx, cy) in &result.changed {
+            let index = self.index(cx, cy).unwrap();
+            self.owners[index] = Some(player);
+        }
+
+        match result.outcome {
+            ActionOutcome::Step(step_result) => step_result,
+            ActionOutcome::Flag(_) => unreachable!(),
+        }
+    }
+
+    /// The player whose `step_as` revealed the spot at the given coordinates, if any. Spots revealed in any other way
+    /// have no owner
+    pub fn owner_of(&self, x: Coord, y: Coord) -> Option<PlayerId> {
+        let index = self.index(x, y)?;
+        self.owners.get(index).copied().flatten()
+    }
+
+    /// Step on every hidden spot inside the rectangle with the given opposite corners `(x0, y0, x1, y1)` (both
+    /// included), in row-major order, like `step_many`
+    pub fn reveal_rect(&mut self, rect: (Coord, Coord, Coord, Coord)) -> BatchStepResult {
+        let (x0, y0, x1, y1) = rect;
+        let coords: Vec<(Coord, Coord)> = self
+            .spots_in_rect(x0, y0, x1, y1)
+            .filter(|(_coords, spot)| spot.is_hidden())
+            .map(|(coords, _spot)| coords)
+            .collect();
+
+        self.step_many(&coords)
+    }
+
+    /// Step on each of the given coordinates in order, stopping at the first step on a mine. Invalid steps (e.g.
+    /// outside the minefield) are reported in the results, and don't stop the batch
+    pub fn step_many(&mut self, coords: &[(Coord, Coord)]) -> BatchStepResult {
+        let mut batch = BatchStepResult { results: Vec::new(), revealed: Vec::new(), boom: None };
+
+        for (i, &(x, y)) in coords.iter().enumerate() {
+            let result = self.apply(Action::Step { x, y });
+            let step_result = match result.outcome {
+                ActionOutcome::Step(step_result) => step_result,
+                ActionOutcome::Flag(_) => unreachable!(),
+            };
+
+            batch.results.push(step_result);
+            batch.revealed.extend(result.changed);
+
+            if step_result == StepResult::Boom {
+                batch.boom = Some((i, (x, y)));
+                break;
+            }
+        }
+
+        batch
+    }
+
+    /// Like `step`, but report why a step can't be taken instead of returning `StepResult::Invalid`. Once the game is
+    /// won or lost, every step is refused. The minefield is not changed when an error is returned
+    pub fn try_step(&mut self, x: Coord, y: Coord) -> Result<StepResult, StepError> {
+        let spot = self.spot(x, y).ok_or(StepError::OutOfBounds { x, y })?;
+
+        if self.game_state() != GameState::InProgress {
+            return Err(StepError::GameOver);
+        }
+
+        if self.locked {
+            return Err(StepError::Locked);
+        }
+
+        if spot.is_flagged() {
+            return Err(StepError::Flagged);
+        }
+
+        if spot.is_revealed() {
+            return Err(StepError::AlreadyRevealed);
+        }
+
+        Ok(self.step(x, y))
+    }
+
+    /// Automatically step on all hidden neighbors (i.e. not flagged) of a revealed spot at the given coordiantes
+    pub fn auto_step(&mut self, x: Coord, y: Coord) -> StepResult {
+        match self.apply(Action::AutoStep { x, y }).outcome {
+            ActionOutcome::Step(step_result) => step_result,
+            ActionOutcome::Flag(_) => unreachable!(),
+        }
+    }
+
+    /// Like `auto_step`, but report why the neighbors can't be stepped on instead of returning `StepResult::Invalid`.
+    /// Once the game is won or lost, every step is refused. The minefield is not changed when an error is returned
+    pub fn try_auto_step(&mut self, x: Coord, y: Coord) -> Result<StepResult, StepError> {
+        let spot = self.spot(x, y).ok_or(StepError::OutOfBounds { x, y })?;
+
+        if self.game_state() != GameState::InProgress {
+            return Err(StepError::GameOver);
+        }
+
+        if self.locked {
+            return Err(StepError::Locked);
+        }
+
+        let SpotState::RevealedEmpty { neighboring_mines } = spot.state else {
+            return Err(StepError::NotRevealedCount);
+        };
+
+        let flags = self.neighbor_summary(x, y).flagged;
+        if flags != neighboring_mines {
+            return Err(StepError::FlagMismatch { flags, neighboring_mines });
+        }
+
+        Ok(self.auto_step(x, y))
+    }
+
+    /// Apply whichever chord the count of a revealed spot allows, looking only at its neighbors: if its flags already
+    /// account for all its mines, step on its other hidden neighbors (like `auto_step`), or else, if its hidden
+    /// neighbors are exactly its missing mines, flag them all. Shown mines count like flags. Returns
+    /// `StepResult::Invalid` if neither applies, or once the game is lost
+    pub fn smart_chord(&mut self, x: Coord, y: Coord) -> StepResult {
+        match self.apply(Action::SmartChord { x, y }).outcome {
+            ActionOutcome::Step(step_result) => step_result,
+            ActionOutcome::Flag(_) => unreachable!(),
+        }
+    }
+
+    /// Step on every hidden neighbor of a revealed spot which can be proven safe by counting around the revealed
+    /// spots, whatever the flags around it. Flags are not trusted, so unlike `auto_step` this never steps on a mine
+    /// next to a wrong flag, and flagged neighbors are left alone. Returns the neighbors actually stepped on, with
+    /// `StepResult::Invalid` if there were none or the minefield is locked
+    pub fn assist_chord(&mut self, x: Coord, y: Coord) -> (StepResult, Vec<(Coord, Coord)>) {
+        let revealed = self.spot(x, y).is_some_and(|spot| spot.is_revealed() && !spot.is_mine());
+        if self.locked || !revealed || self.game_state() != GameState::InProgress {
+            return (StepResult::Invalid, Vec::new());
+        }
+
+        let (safe, _) = self.certain_spots();
+        let mut neighbors: Vec<usize> = self.neighbor_indices(self.index(x, y).unwrap())
+            .filter(|n_index| safe.contains(n_index))
+            .collect();
+        neighbors.sort_unstable();
+
+        let mut step_result = StepResult::Invalid;
+        let mut stepped = Vec::new();
+        for n_index in neighbors {
+            // An earlier step may have already flooded this neighbor
+            if self.get(n_index).is_hidden() {
+                let (nx, ny) = self.coords(n_index);
+                let result = self.apply(Action::Step { x: nx, y: ny });
+                if result.changed.is_empty() {
+                    continue;
+                }
+
+                stepped.push((nx, ny));
+                match result.outcome {
+                    ActionOutcome::Step(StepResult::Boom) => {
+                        step_result = StepResult::Boom;
+                        break;
+                    }
+                    ActionOutcome::Step(StepResult::Phew) => step_result = StepResult::Phew,
+                    _ => {}
+                }
+            }
+        }
+
+        (step_result, stepped)
+    }
+
+    /// Automatically step around a revealed spot like `auto_step`, and then keep automatically stepping around every
+    /// spot revealed on the way whose flags already account for all its mines, until no such spot is left or a mine
+    /// explodes. Each spot is automatically stepped around at most once. Every automatic step is a separate action
+    pub fn auto_step_cascade(&mut self, x: Coord, y: Coord) -> BatchStepResult {
+        let mut batch = BatchStepResult { results: Vec::new(), revealed: Vec::new(), boom: None };
+        let mut visited = HashSet::new();
+        let mut spots_to_visit = VecDeque::from([(x, y)]);
+
+        while let Some((vx, vy)) = spots_to_visit.pop_front() {
+            if !visited.insert((vx, vy)) {
+                continue;
+            }
+
+            let result = self.apply(Action::AutoStep { x: vx, y: vy });
+            let step_result = match result.outcome {
+                ActionOutcome::Step(step_result) => step_result,
+                ActionOutcome::Flag(_) => unreachable!(),
+            };
+
+            batch.results.push(step_result);
+
+            if step_result == StepResult::Boom {
+                batch.revealed.extend(result.changed);
+                batch.boom = Some((batch.results.len() - 1, self.exploded_at.unwrap()));
+                break;
+            }
+
+            for &(cx, cy) in &result.changed {
+                let satisfied = self.unflagged_mine_count(cx, cy) == Some(0);
+
+                if satisfied && self.neighbor_summary(cx, cy).hidden > 0 && !visited.contains(&(cx, cy)) {
+                    spots_to_visit.push_back((cx, cy));
+                }
+            }
+
+            batch.revealed.extend(result.changed);
+        }
+
+        batch
+    }
+
+    /// Apply `smart_chord` on the revealed spot at the given coordinates, and then on every revealed spot around the
+    /// spots it changed, and so on, until no smart chord around the changed spots changes anything, or a mine explodes
+    /// (which only happens around wrong flags). Only the neighborhood of the given spot is looked at, rather than the
+    /// whole minefield. Returns the number of spots flagged or revealed
+    pub fn deduce_around(&mut self, x: Coord, y: Coord) -> usize {
+        let mut changed_count = 0;
+        let mut spots_to_visit = VecDeque::from([(x, y)]);
+
+        while let Some((vx, vy)) = spots_to_visit.pop_front() {
+            let result = self.apply(Action::SmartChord { x: vx, y: vy });
+            changed_count += result.changed.len();
+
+            if result.outcome == ActionOutcome::Step(StepResult::Boom) {
+                break;
+            }
+
+            // The spots whose neighborhood changed may allow new chords
+            for &(cx, cy) in &result.changed {
+                let affected = std::iter::once((cx, cy)).chain(self.neighbors_coords(cx, cy));
+
+                for (ax, ay) in affected {
+                    if self.spot(ax, ay).is_some_and(|spot| spot.is_revealed()) && !spots_to_visit.contains(&(ax, ay)) {
+                        spots_to_visit.push_back((ax, ay));
+                    }
+                }
+            }
+        }
+
+        changed_count
+    }
+
+    /// Apply once every move forced by simple counting around the revealed spots, as they are when called: flag the
+    /// hidden neighbors of every count which needs all of them as mines, then step on the hidden neighbors of every
+    /// count whose flags account for all its mines. The flags of the player are trusted, so this never steps on a
+    /// mine while they are all correct, but it may step on one next to a wrong flag, and then it stops. Calling this
+    /// until the report is empty flags and chords to a fixed point
+    pub fn solve_step(&mut self) -> SolveStepReport {
+        let mut to_flag = HashSet::new();
+        let mut to_step = HashSet::new();
+
+        for index in self.indices() {
+            let SpotState::RevealedEmpty { neighboring_mines } = self.get(index).state else {
+                continue;
+            };
+
+            let (x, y) = self.coords(index);
+            let summary = self.neighbor_summary(x, y);
+
+            if summary.hidden == 0 {
+                continue;
+            }
+
+            let hidden = self.neighbor_indices(index).filter(|&n_index| self.get(n_index).is_hidden());
+
+            if summary.flagged + summary.mines_shown == neighboring_mines {
+                to_step.extend(hidden);
+            } else if summary.flagged + summary.mines_shown + summary.hidden == neighboring_mines {
+                to_flag.extend(hidden);
+            }
+        }
+
+        let mut report = SolveStepReport::default();
+        let mut to_flag: Vec<usize> = to_flag.into_iter().collect();
+        let mut to_step: Vec<usize> = to_step.into_iter().collect();
+        to_flag.sort_unstable();
+        to_step.sort_unstable();
+
+        for index in to_flag {
+            let (x, y) = self.coords(index);
+
+            // A question mark may take two toggles to become a flag
+            while !self.get(index).is_flagged() {
+                match self.toggle_flag(x, y) {
+                    FlagToggleResult::None | FlagToggleResult::LimitReached => break,
+                    _ => {},
+                }
+            }
+
+            if self.get(index).is_flagged() {
+                report.flagged.push((x, y));
+            }
+        }
+
+        for index in to_step {
+            let (x, y) = self.coords(index);
+            let result = self.apply(Action::Step { x, y });
+            report.revealed.extend(result.changed);
+
+            if result.outcome == ActionOutcome::Step(StepResult::Boom) {
+                break;
+            }
+        }
+
+        report
+    }
+
+    /// Check if the minefield has been cleared
+    pub fn is_cleared(&self) -> bool {
+        for (_spot_coords, spot) in self.spots() {
+            if !spot.is_resolved() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Once every empty spot has been revealed, flag all the remaining hidden mines, which clears the minefield.
+    /// Returns the number of flags placed, which is zero if any empty spot is still hidden or if a mine has exploded
+    pub fn flag_remaining_mines(&mut self) -> usize {
+        self.flag_all_mines().len()
+    }
+
+    /// Once the game is won, flag all the remaining hidden (or questioned) mines, which clears the minefield, and
+    /// return their coordinates `(x, y)` in row-major order, e.g. to animate them. Mines are only flagged once the game
+    /// is won, so this can't give them away: nothing is flagged while the game is in progress or after it was lost.
+    /// Tidying up a won game isn't a move, so this also works on a locked minefield
+    pub fn flag_all_mines(&mut self) -> Vec<(Coord, Coord)> {
+        if self.game_state() != GameState::Won {
+            return Vec::new();
+        }
+
+        let hidden_mines: Vec<usize> = self
+            .indices()
+            .filter(|&index| matches!(self.get(index).state, SpotState::HiddenMine | SpotState::QuestionedMine))
+            .collect();
+
+        for &index in &hidden_mines {
+            self.update(index, |spot| spot.state = SpotState::FlaggedMine);
+        }
+
+        hidden_mines.into_iter().map(|index| self.coords(index)).collect()
+    }
+
+    /// The number of steps, auto steps and flag toggles which changed the minefield so far
+    pub fn move_count(&self) -> u32 {
+        self.moves
+    }
+
+    /// Whether any step (or auto step) has revealed or exploded a spot yet, e.g. to decide when to start a timer
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// The coordinates of the mine which exploded, if any. Should several mines explode, the most recent is returned,
+    /// except on decoded minefields, which don't know the order of the explosions (see `from_bytes`)
+    pub fn exploded_at(&self) -> Option<(Coord, Coord)> {
+        self.exploded_at
+    }
+
+    /// The coordinates of every mine which exploded, in row-major order
+    pub fn exploded_mines(&self) -> Vec<(Coord, Coord)> {
+        self.spots()
+            .filter(|(_coords, spot)| spot.is_exploded())
+            .map(|(coords, _spot)| coords)
+            .collect()
+    }
+
+    /// The state of the game: lost as soon as a mine explodes, won once every empty spot has been revealed without
+    /// showing any mine (e.g. with `reveal_all`), and in progress otherwise
+    pub fn game_state(&self) -> GameState {
+        if self.exploded_at.is_some() {
+            GameState::Lost
+        } else if self.safe_spots_remaining() == 0 && self.shown == self.revealed {
+            GameState::Won
+        } else {
+            GameState::InProgress
+        }
+    }
+
+    /// Debug (or cheat) feature, not meant for normal play: reveal the whole minefield at once. Every empty spot
+    /// becomes a `RevealedEmpty` and every mine which didn't explode becomes a `RevealedMine`, dropping all flags and
+    /// question marks. This is not a player action, so it isn't recorded in the history, and it doesn't lose the game.
+    /// Neither does it win it: the game stays in progress while mines are shown
+    pub fn reveal_all(&mut self) {
+        for index in self.indices().collect::<Vec<_>>() {
+            self.update(index, |spot| {
+                spot.state = match spot.state {
+                    SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::FlaggedEmpty { neighboring_mines } |
+                    SpotState::WrongFlag { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } => SpotState::RevealedEmpty { neighboring_mines },
+                    SpotState::HiddenMine | SpotState::FlaggedMine | SpotState::QuestionedMine => {
+                        SpotState::RevealedMine
+                    },
+                    state => state,
+                }
+            });
+        }
+    }
+
+    /// Show the post-mortem board of a lost game: every flag on an empty spot becomes a `WrongFlag`, and every hidden
+    /// mine becomes a `RevealedMine`. Does nothing unless the game is lost
+    pub fn finalize_loss(&mut self) {
+        if self.game_state() != GameState::Lost {
+            return;
+        }
+
+        for index in self.indices().collect::<Vec<_>>() {
+            self.update(index, |spot| {
+                match spot.state {
+                    SpotState::FlaggedEmpty { neighboring_mines } => {
+                        spot.state = SpotState::WrongFlag { neighboring_mines };
+                    },
+                    SpotState::HiddenMine | SpotState::QuestionedMine => spot.state = SpotState::RevealedMine,
+                    _ => {},
+                }
+            });
+        }
+    }
+
+    /// Count the placed flags as `(correct_flags, incorrect_flags)`, where a correct flag sits on a mine and an
+    /// incorrect one sits on an empty spot
+    pub fn flag_accuracy(&self) -> (u32, u32) {
+        let mut correct_flags = 0;
+        let mut incorrect_flags = 0;
+
+        for (_spot_coords, spot) in self.spots() {
+            match spot.state {
+                SpotState::FlaggedMine => correct_flags += 1,
+                SpotState::FlaggedEmpty { neighboring_mines: _ } |
+                SpotState::WrongFlag { neighboring_mines: _ } => incorrect_flags += 1,
+                _ => {},
+            }
+        }
+
+        (correct_flags, incorrect_flags)
+    }
+
+    /// Set a flag on a hidden spot, or clear the flag if the spot had one, or do nothing if
+    /// the spot cannot be flagged
+    pub fn toggle_flag(&mut self, x: Coord, y: Coord) -> FlagToggleResult {
+        match self.apply(Action::ToggleFlag { x, y }).outcome {
+            ActionOutcome::Flag(flag_result) => flag_result,
+            ActionOutcome::Step(_) => unreachable!(),
+        }
+    }
+
+    /// Reveal a single spot for a player who is stuck: a hidden spot which is provably safe from what the player can
+    /// see, or, if there is none, the hidden spot least likely to hold a mine. Flags are the player's guesses, so they
+    /// are not trusted. Returns `None` if the game is over, or if no hidden spot is left to step on
+    pub fn hint(&mut self) -> Option<Hint> {
+        if self.game_state() != GameState::InProgress {
+            return None;
+        }
+
+        let (safe, mines) = self.certain_spots();
+
+        if let Some(&index) = safe.iter().find(|&&index| self.get(index).is_hidden()) {
+            let (x, y) = self.coords(index);
+            let _step_result = self.step(x, y);
+            debug_assert_eq!(_step_result, StepResult::Phew);
+
+            return Some(Hint::Safe { x, y });
+        }
+
+        let (index, _probability) = self
+            .mine_likelihoods()
+            .into_iter()
+            .filter(|&(index, _probability)| self.get(index).is_hidden() && !mines.contains(&index))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        let (x, y) = self.coords(index);
+        let result = self.step(x, y);
+
+        Some(Hint::Guess { x, y, result })
+    }
+
+    /// Point out a hidden spot which is provably safe or provably holds a mine from what the player can see, together
+    /// with the revealed counts which prove it, without changing the minefield. The proof uses the same deductions as
+    /// `deduce` (the count of a revealed spot on its own, or a pair of counts where one sees a subset of the unknown
+    /// spots the other sees), and includes the counts proving the other spots it relies on. Flags are not trusted.
+    /// Returns `None` if the game is over, or if nothing is provable
+    pub fn hint_explained(&self) -> Option<ExplainedHint> {
+        if self.game_state() != GameState::InProgress {
+            return None;
+        }
+
+        // The spot decided first has the shortest proof
+        let proofs = self.proofs();
+        let (&index, proof) = proofs
+            .iter()
+            .filter(|(&index, _proof)| self.get(index).is_hidden())
+            .min_by_key(|(&index, proof)| (proof.pass, index))?;
+
+        // Gather the counts of the proof, and of the proofs of the spots decided earlier around them
+        let mut because = Vec::new();
+        let mut spots_to_explain = vec![index];
+        let mut explained = HashSet::from([index]);
+
+        while let Some(spot_index) = spots_to_explain.pop() {
+            let spot_proof = &proofs[&spot_index];
+
+            for &constraint in &spot_proof.constraints {
+                if because.contains(&constraint) {
+                    continue;
+                }
+
+                because.push(constraint);
+
+                for n_index in self.neighbor_indices(constraint) {
+                    if proofs.get(&n_index).is_some_and(|n_proof| n_proof.pass < spot_proof.pass) &&
+                        explained.insert(n_index) {
+                        spots_to_explain.push(n_index);
+                    }
+                }
+            }
+        }
+
+        because.sort_unstable();
+
+        Some(ExplainedHint {
+            coords: self.coords(index),
+            kind: if proof.mine { HintKind::Mine } else { HintKind::Safe },
+            because: because
+                .into_iter()
+                .map(|constraint| ConstraintRef {
+                    coords: self.coords(constraint),
+                    neighboring_mines: self.get(constraint).neighboring_mines().unwrap(),
+                })
+                .collect(),
+        })
+    }
+
+    /// The unknown spots which are provably safe, and those which provably hold a mine, from what the player can see.
+    /// Flags are the player's guesses, so they are not trusted: flagged spots show up in either list too
+    pub fn deduce(&self) -> Deductions {
+        let (safe, mines) = self.certain_spots();
+        let to_coords = |set: HashSet<usize>| {
+            let mut indices: Vec<usize> = set.into_iter().collect();
+            indices.sort_unstable();
+            indices.into_iter().map(|index| self.coords(index)).collect()
+        };
+
+        Deductions { safe: to_coords(safe), mines: to_coords(mines) }
+    }
+
+    /// For a revealed empty spot, the count of its neighboring mines which are not flagged yet (i.e. its count minus
+    /// the flags around it, down to zero). `None` for any other spot
+    pub fn unflagged_mine_count(&self, x: Coord, y: Coord) -> Option<u8> {
+        match self.spot(x, y)?.state {
+            SpotState::RevealedEmpty { neighboring_mines } => {
+                let placed_flags = self.neighbors(x, y).filter(|(_coords, spot)| spot.is_flagged()).count() as u8;
+                Some(neighboring_mines.saturating_sub(placed_flags))
+            },
+            _ => None,
+        }
+    }
+
+    /// Regions of connected hidden (or flagged) spots which don't touch any revealed spot, i.e. pockets of the field
+    /// which the player knows nothing about
+    pub fn hidden_islands(&self) -> Vec<Vec<(Coord, Coord)>> {
+        self.connected_regions(|index| {
+            Self::is_unknown(self.get(index)) &&
+                !self.neighbor_indices(index).any(|n_index| self.get(n_index).is_revealed())
+        })
+    }
+
+    /// Regions of connected hidden (or flagged) spots, e.g. the independent pockets left in the endgame, each starting
+    /// from its first spot in row-major order. Only what the player can see is used, not where the mines are
+    pub fn hidden_regions(&self) -> Vec<Vec<(Coord, Coord)>> {
+        self.connected_regions(|index| Self::is_unknown(self.get(index)))
+    }
+
+    /// Number of `hidden_regions`, counted without collecting their coordinates
+    pub fn hidden_region_count(&self) -> usize {
+        let mut visited = vec![false; self.field.len()];
+        let mut count = 0;
+
+        for index in self.indices() {
+            if visited[index] || !Self::is_unknown(self.get(index)) {
+                continue;
+            }
+
+            // Visit the whole region by flooding from its first spot
+            count += 1;
+            visited[index] = true;
+            let mut spots_to_visit = vec![index];
+
+            while let Some(v_index) = spots_to_visit.pop() {
+                for n_index in self.neighbor_indices(v_index) {
+                    if !visited[n_index] && Self::is_unknown(self.get(n_index)) {
+                        visited[n_index] = true;
+                        spots_to_visit.push(n_index);
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Coordinates `(x, y)` of every empty spot with no neighboring mines, in row-major order, whether revealed or not.
+    /// Stepping on any of them opens the area around it. This reads the hidden layout, so it is meant for analysis
+    pub fn zero_cells(&self) -> Vec<(Coord, Coord)> {
+        self.indices()
+            .filter(|&index| self.get(index).neighboring_mines() == Some(0))
+            .map(|index| self.coords(index))
+            .collect()
+    }
+
+    /// Coordinates `(x, y)` of every empty spot with at least one neighboring mine, whether revealed or not, e.g. to
+    /// highlight the dangerous parts of the field. This reads the hidden layout, so it is meant for analysis
+    pub fn cells_adjacent_to_mines(&self) -> HashSet<(Coord, Coord)> {
+        self.indices()
+            .filter(|&index| self.get(index).neighboring_mines().is_some_and(|neighboring_mines| neighboring_mines > 0))
+            .map(|index| self.coords(index))
+            .collect()
+    }
+
+    /// Number of empty spots with each count of neighboring mines, from 0 to 8, whether revealed or not. This reads the
+    /// hidden layout, so it is meant for analysis
+    pub fn count_distribution(&self) -> [usize; 9] {
+        let mut distribution = [0; 9];
+        for neighboring_mines in self.indices().filter_map(|index| self.get(index).neighboring_mines()) {
+            distribution[neighboring_mines as usize] += 1;
+        }
+
+        distribution
+    }
+
+    /// The 3BV of the mine layout: the minimum number of steps needed to clear the minefield, which is one step per
+    /// opening (a region of connected empty spots with no neighboring mines) plus one step per empty spot which no
+    /// opening reveals. This reads the hidden layout, so it is meant for analysis rather than for a fair client
+    /// mid-game
+    pub fn bbbv(&self) -> usize {
+        let openings = self.openings();
+        let revealed_by_openings: HashSet<(Coord, Coord)> = openings.iter().flatten().copied().collect();
+
+        let unopened = self
+            .spots()
+            .filter(|(coords, spot)| !spot.is_mine() && !revealed_by_openings.contains(coords))
+            .count();
+
+        openings.len() + unopened
+    }
+
+    /// Statistics about the mine layout, for difficulty analysis. This reads the hidden layout, so it is meant to be
+    /// used before a game or for analysis, rather than for a fair client mid-game
+    pub fn layout_stats(&self) -> LayoutStats {
+        // Empty spots grouped into regions enclosed by mines and the edges of the field
+        let safe_regions = self.connected_regions(|index| !self.get(index).is_mine());
+        let isolated_regions = safe_regions
+            .iter()
+            .filter(|region| {
+                region
+                    .iter()
+                    .all(|&(x, y)| self.spot(x, y).unwrap().neighboring_mines() != Some(0))
+            })
+            .count();
+
+        let openings = self.openings();
+        let cell_count = self.cell_count();
+
+        LayoutStats {
+            openings: openings.len(),
+            largest_opening: openings.iter().map(Vec::len).max().unwrap_or(0),
+            isolated_regions,
+            density: if cell_count > 0 { self.mines as f64 / cell_count as f64 } else { 0.0 },
+        }
+    }
+
+    /// How many times a player who only steps on provably safe spots (as far as `deduce()` can tell) is forced to
+    /// guess while clearing the minefield, starting from the largest opening. A score of 0 means the minefield can be
+    /// cleared without guessing. The simulated player always guesses the spot least likely to hold a mine among the
+    /// empty ones, so this counts the guesses, not the risk they carry. This reads the hidden layout, and plays on a
+    /// copy of the minefield with every spot hidden
+    pub fn guess_difficulty(&self) -> u32 {
+        let mut game = self.hidden_copy();
+
+        // Without any opening, the very first step is a guess
+        if let Some(opening) = self.openings().into_iter().max_by_key(Vec::len) {
+            game.step(opening[0].0, opening[0].1);
+        }
+
+        game.play_out_guessing()
+    }
+
+    /// How many times a player who only steps on provably safe spots (as far as `deduce()` can tell) is forced to
+    /// guess while clearing the minefield, after first stepping on the `start` coordinates. Like `guess_difficulty`,
+    /// each guess is the empty spot least likely to hold a mine, so the same minefield and start always give the same
+    /// count. If the start is not an empty spot, the first step counts as a guess. The minefield is not changed
+    pub fn forced_guesses(&self, start: (Coord, Coord)) -> u32 {
+        let mut game = self.hidden_copy();
+
+        if self.spot(start.0, start.1).is_some_and(|spot| !spot.is_mine()) {
+            game.step(start.0, start.1);
+        }
+
+        game.play_out_guessing()
+    }
+
+    /// Size of the largest region of connected revealed spots
+    pub fn largest_revealed_region(&self) -> usize {
+        self.connected_regions(|index| {
+            matches!(self.get(index).state, SpotState::RevealedEmpty { neighboring_mines: _ })
+        })
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Every complete placement of mines which agrees with what the player can see: the counts shown by revealed spots,
+    /// the exploded mines, and the total number of mines. Flags are the player's guesses, so flagged spots are treated
+    /// like any other hidden spot.
+    ///
+    /// The cost of the search is exponential in the number of hidden spots, so this is only meant for tiny fields, e.g.
+    /// as a reference when testing solvers.
+    pub fn enumerate_consistent_mines(&self) -> Vec<HashSet<(Coord, Coord)>> {
+        let mut unknown: Vec<(Coord, Coord)> = Vec::new();
+        let mut known_mines: Vec<(Coord, Coord)> = Vec::new();
+
+        for ((x, y), spot) in self.spots() {
+            match spot.state {
+                SpotState::HiddenEmpty { neighboring_mines: _ } |
+                SpotState::HiddenMine |
+                SpotState::FlaggedEmpty { neighboring_mines: _ } |
+                SpotState::FlaggedMine |
+                SpotState::QuestionedEmpty { neighboring_mines: _ } |
+                SpotState::QuestionedMine => unknown.push((x, y)),
+                SpotState::ExplodedMine | SpotState::RevealedMine => known_mines.push((x, y)),
+                SpotState::RevealedEmpty { neighboring_mines: _ } | SpotState::WrongFlag { neighboring_mines: _ } => {},
+            }
+        }
+
+        // Each revealed count is a constraint on the number of mines among its unknown neighbors
+        let mut search = Search {
+            needed: Vec::new(),
+            unassigned: Vec::new(),
+            touching: vec![Vec::new(); unknown.len()],
+        };
+
+        for ((x, y), spot) in self.spots() {
+            if let SpotState::RevealedEmpty { neighboring_mines } = spot.state {
+                let constraint = search.needed.len();
+                let mut needed = neighboring_mines as i32;
+                let mut unassigned = 0;
+
+                for (n_coords, n_spot) in self.neighbors(x, y) {
+                    if n_spot.state == SpotState::ExplodedMine {
+                        needed -= 1;
+                    } else if let Some(u) = unknown.iter().position(|coords| *coords == n_coords) {
+                        search.touching[u].push(constraint);
+                        unassigned += 1;
+                    }
+                }
+
+                search.needed.push(needed);
+                search.unassigned.push(unassigned);
+            }
+        }
+
+        let mines_left = self.mines as i32 - known_mines.len() as i32;
+        let mut assignment = vec![false; unknown.len()];
+        let mut arrangements = Vec::new();
+
+        search.search(0, mines_left, &mut assignment, &mut |assignment| {
+            let arrangement = unknown
+                .iter()
+                .zip(assignment)
+                .filter(|(_coords, is_mine)| **is_mine)
+                .map(|(coords, _is_mine)| *coords)
+                .chain(known_mines.iter().copied())
+                .collect();
+
+            arrangements.push(arrangement);
+            false
+        });
+
+        arrangements
+    }
+
+    /// Check whether the flags placed by the player can all be right, from what the player can see. Every revealed
+    /// count must have no more flags (and shown mines) around it than its count, and enough hidden neighbors for the
+    /// rest, and there must be no more flags than mines. When the hidden spots next to revealed spots are at most
+    /// `CONSISTENCY_FRONTIER_LIMIT`, a search also checks that some placement of the remaining mines satisfies all
+    /// counts at once
+    pub fn is_consistent(&self) -> ConsistencyReport {
+        // Flags are trusted, on top of the mines the player was shown
+        let placed_mine = |spot: Spot| {
+            matches!(
+                spot.state,
+                SpotState::FlaggedEmpty { neighboring_mines: _ } |
+                    SpotState::FlaggedMine |
+                    SpotState::ExplodedMine |
+                    SpotState::RevealedMine
+            )
+        };
+
+        let placed = self.spots().filter(|(_coords, spot)| placed_mine(*spot)).count() as u32;
+        if placed > self.mines {
+            return ConsistencyReport::TooManyFlags { flags: placed, mines: self.mines };
+        }
+
+        // Each revealed count is a constraint on the number of mines among its hidden neighbors
+        let mut frontier: HashMap<usize, usize> = HashMap::new();
+        let mut search = Search { needed: Vec::new(), unassigned: Vec::new(), touching: Vec::new() };
+
+        for index in self.indices() {
+            let SpotState::RevealedEmpty { neighboring_mines } = self.get(index).state else {
+                continue;
+            };
+
+            let placed_around = self.neighbor_indices(index).filter(|&n_index| placed_mine(self.get(n_index))).count();
+            let hidden: Vec<usize> = self
+                .neighbor_indices(index)
+                .filter(|&n_index| self.get(n_index).is_hidden())
+                .collect();
+
+            if placed_around > neighboring_mines as usize || placed_around + hidden.len() < neighboring_mines as usize {
+                let (x, y) = self.coords(index);
+                return ConsistencyReport::Violated { x, y };
+            }
+
+            let constraint = search.needed.len();
+            search.needed.push((neighboring_mines as usize - placed_around) as i32);
+            search.unassigned.push(hidden.len() as i32);
+
+            for h_index in hidden {
+                let position = *frontier.entry(h_index).or_insert_with(|| {
+                    search.touching.push(Vec::new());
+                    search.touching.len() - 1
+                });
+                search.touching[position].push(constraint);
+            }
+        }
+
+        if frontier.len() > Self::CONSISTENCY_FRONTIER_LIMIT {
+            return ConsistencyReport::LocallyConsistent;
+        }
+
+        // The mines not on the frontier can go anywhere else
+        let mines_left = (self.mines - placed) as usize;
+        let interior = self.spots().filter(|(_coords, spot)| spot.is_hidden()).count() - frontier.len();
+        let mut assignment = vec![false; frontier.len()];
+
+        for frontier_mines in mines_left.saturating_sub(interior)..=mines_left.min(frontier.len()) {
+            if search.search(0, frontier_mines as i32, &mut assignment, &mut |_assignment| true) {
+                return ConsistencyReport::Consistent;
+            }
+        }
+
+        ConsistencyReport::Contradiction
+    }
+
+    /// The fewest and the most mines the unknown spots (hidden or flagged, as flags are not trusted) can hold, as far
+    /// as the revealed counts and the number of mines not yet shown tell. When both bounds equal the number of unknown
+    /// spots, every unknown spot holds a mine. The bounds are exact when the unknown spots next to revealed counts are
+    /// at most `CONSISTENCY_FRONTIER_LIMIT`; otherwise each revealed count is only looked at on its own
+    pub fn remaining_mine_bounds(&self) -> (u32, u32) {
+        let shown_mines = self.spots().filter(|(_coords, spot)| spot.is_revealed() && spot.is_mine()).count() as u32;
+        let mines_left = self.mines.saturating_sub(shown_mines);
+
+        // Each revealed count is a constraint on the number of mines among its unknown neighbors
+        let mut frontier: HashMap<usize, usize> = HashMap::new();
+        let mut search = Search { needed: Vec::new(), unassigned: Vec::new(), touching: Vec::new() };
+
+        for index in self.indices() {
+            let SpotState::RevealedEmpty { neighboring_mines } = self.get(index).state else {
+                continue;
+            };
+
+            let shown_around = self
+                .neighbor_indices(index)
+                .filter(|&n_index| self.get(n_index).is_revealed() && self.get(n_index).is_mine())
+                .count() as i32;
+            let unknown: Vec<usize> = self
+                .neighbor_indices(index)
+                .filter(|&n_index| Self::is_unknown(self.get(n_index)))
+                .collect();
+
+            let constraint = search.needed.len();
+            search.needed.push(neighboring_mines as i32 - shown_around);
+            search.unassigned.push(unknown.len() as i32);
+
+            for u_index in unknown {
+                let position = *frontier.entry(u_index).or_insert_with(|| {
+                    search.touching.push(Vec::new());
+                    search.touching.len() - 1
+                });
+                search.touching[position].push(constraint);
+            }
+        }
+
+        let unknown = self.spots().filter(|(_coords, spot)| Self::is_unknown(*spot)).count() as u32;
+        let interior = unknown - frontier.len() as u32;
+
+        let (frontier_min, frontier_max) = if frontier.len() > Self::CONSISTENCY_FRONTIER_LIMIT {
+            let most_needed = search.needed.iter().copied().max().unwrap_or(0).max(0) as u32;
+            (most_needed, frontier.len() as u32)
+        } else {
+            let mut assignment = vec![false; frontier.len()];
+            let feasible: Vec<u32> = (0..=frontier.len() as u32)
+                .filter(|&frontier_mines| {
+                    search.search(0, frontier_mines as i32, &mut assignment, &mut |_assignment| true)
+                })
+                .collect();
+
+            match (feasible.first(), feasible.last()) {
+                (Some(&min), Some(&max)) => (min, max),
+                _ => (0, frontier.len() as u32),
+            }
+        };
+
+        (frontier_min.min(mines_left), (frontier_max + interior).min(mines_left))
+    }
+
+    /// Whether some revealed count can't be satisfied by its neighbors: it has more flags (and shown mines) around it
+    /// than its count, or fewer hidden and flagged neighbors than the mines it still needs. Unlike `is_consistent`,
+    /// this only looks at each count on its own
+    pub fn has_contradiction(&self) -> bool {
+        self.spots().any(|((x, y), spot)| {
+            let SpotState::RevealedEmpty { neighboring_mines } = spot.state else {
+                return false;
+            };
+
+            let summary = self.neighbor_summary(x, y);
+            let placed = summary.flagged + summary.mines_shown;
+
+            placed > neighboring_mines || placed + summary.hidden < neighboring_mines
+        })
+    }
+
+    /// A copy of the minefield as seen by the player, which does not leak the positions of hidden mines. Every
+    /// `HiddenMine` becomes a `HiddenEmpty`, every `FlaggedMine` becomes a `FlaggedEmpty` and every `QuestionedMine`
+    /// becomes a `QuestionedEmpty`, each with the true count of neighboring mines
+    pub fn player_view(&self) -> Minefield<T>
+    where
+        T: Clone,
+    {
+        let mut view = self.clone();
+
+        for index in self.indices() {
+            let state = self.visible_state(index);
+
+            if state != self.get(index).state {
+                view.update(index, |spot| spot.state = state);
+            }
+        }
+
+        view
+    }
+
+    /// An owned snapshot of the state of every coordinate of the field grid, in row-major order: the outer index is
+    /// `y`, increasing downward, and the inner index is `x`, increasing to the right, so the state at `(x, y)` is
+    /// `grid[y][x]`. Holes are `None`. This holds the true state of every spot, hidden mines included; use
+    /// `to_grid_visible` to show the grid to the player
+    pub fn to_grid(&self) -> Vec<Vec<Option<SpotState>>> {
+        self.grid_with(|index| self.get(index).state)
+    }
+
+    /// Like `to_grid`, with the states of `player_view`, which don't leak the positions of hidden mines
+    pub fn to_grid_visible(&self) -> Vec<Vec<Option<SpotState>>> {
+        self.grid_with(|index| self.visible_state(index))
+    }
+
+    /// What the player sees at every coordinate of the field grid, one byte per coordinate in row-major order, e.g. to
+    /// hand the whole minefield to JavaScript as a `Uint8Array`. The bytes are:
+    /// - `0` to `8`: a revealed empty spot, with its count of neighboring mines
+    /// - `9`: a hidden spot, mine or not
+    /// - `10`: a flagged spot
+    /// - `11`: a spot marked with a question mark
+    /// - `12`: a mine shown after the game was lost
+    /// - `13`: an exploded mine
+    /// - `14`: a flag shown to be wrong after the game was lost
+    /// - `15`: a hole
+    pub fn state_flat(&self) -> Vec<u8> {
+        self.field
+            .iter()
+            .map(|&packed| {
+                if packed == PackedSpot::HOLE {
+                    return 15;
+                }
+
+                match Spot::from(packed).state {
+                    SpotState::RevealedEmpty { neighboring_mines } => neighboring_mines,
+                    SpotState::HiddenEmpty { neighboring_mines: _ } | SpotState::HiddenMine => 9,
+                    SpotState::FlaggedEmpty { neighboring_mines: _ } | SpotState::FlaggedMine => 10,
+                    SpotState::QuestionedEmpty { neighboring_mines: _ } | SpotState::QuestionedMine => 11,
+                    SpotState::RevealedMine => 12,
+                    SpotState::ExplodedMine => 13,
+                    SpotState::WrongFlag { neighboring_mines: _ } => 14,
+                }
+            })
+            .collect()
+    }
+
+    /// Transform every coordinate of the field grid into a value of any type, in one pass, and arrange the values in a
+    /// row-major grid like `to_grid`: the value for `(x, y)` is `grid[y][x]`. `f` is given the coordinates and their
+    /// spot, or `None` for holes
+    pub fn map_grid<U>(&self, f: impl Fn((Coord, Coord), Option<Spot>) -> U) -> Vec<Vec<U>> {
+        (0..self.height)
+            .map(|y| (0..self.width).map(|x| f((x, y), self.spot(x, y))).collect())
+            .collect()
+    }
+
+    /// Check the internal consistency of the minefield: every coordinate of the field grid has exactly one spot, every
+    /// empty spot holds the correct count of neighboring mines, and the number of mines matches the mines in the field.
+    /// All inconsistencies found are reported
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let expected_spots = self.width as usize * self.height as usize;
+
+        if self.field.len() != expected_spots {
+            // Coordinates can't be trusted without the right number of spots
+            return Err(vec![ValidationError::SpotCount { stored: self.field.len(), actual: expected_spots }]);
+        }
+
+        let mut errors = Vec::new();
+        let mut mines = 0;
+
+        for ((x, y), spot) in self.spots() {
+            match spot.state {
+                SpotState::HiddenEmpty { neighboring_mines } |
+                SpotState::FlaggedEmpty { neighboring_mines } |
+                SpotState::RevealedEmpty { neighboring_mines } |
+                SpotState::WrongFlag { neighboring_mines } |
+                SpotState::QuestionedEmpty { neighboring_mines } => {
+                    let actual = self.neighboring_mines(x, y);
+
+                    if neighboring_mines != actual {
+                        errors.push(ValidationError::NeighborCount { x, y, stored: neighboring_mines, actual });
+                    }
+                },
+                SpotState::HiddenMine |
+                SpotState::FlaggedMine |
+                SpotState::ExplodedMine |
+                SpotState::RevealedMine |
+                SpotState::QuestionedMine => {
+                    mines += 1;
+                },
+            }
+        }
+
+        if self.mines != mines {
+            errors.push(ValidationError::MineCount { stored: self.mines, actual: mines });
+        }
+
+        let flags = self.spots().filter(|(_coords, spot)| spot.is_flagged()).count() as u32;
+
+        if self.flags != flags {
+            errors.push(ValidationError::FlagCount { stored: self.flags, actual: flags });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Recalculate the count of neighboring mines of every empty spot from the actual positions of the mines
+    pub fn recompute_counts(&mut self) {
+        for index in self.indices().collect::<Vec<_>>() {
+            let (x, y) = self.coords(index);
+            let actual = self.neighboring_mines(x, y);
+
+            self.update(index, |spot| {
+                match &mut spot.state {
+                    SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::FlaggedEmpty { neighboring_mines } |
+                    SpotState::RevealedEmpty { neighboring_mines } |
+                    SpotState::WrongFlag { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } => {
+                        *neighboring_mines = actual;
+                    },
+                    _ => {},
+                }
+            });
+        }
+    }
+
+    /// List the coordinates of every spot whose state differs in the `other` minefield, together with its state in the
+    /// `other` minefield, in row-major order. Both minefields must have the same dimensions and shape
+    pub fn diff(&self, other: &Minefield<T>) -> Result<StateDiff, DiffError> {
+        if !self.same_shape(other) {
+            return Err(DiffError::ShapeMismatch);
+        }
+
+        // Nothing to look for if both fields are byte-for-byte identical
+        if self.field == other.field {
+            return Ok(Vec::new());
+        }
+
+        let diff = self
+            .spots()
+            .zip(other.spots())
+            .filter(|((_coords, spot), (_other_coords, other_spot))| spot != other_spot)
+            .map(|((_coords, _spot), (other_coords, other_spot))| (other_coords, other_spot.state))
+            .collect();
+
+        Ok(diff)
+    }
+
+    /// Patch the state of spots with the given list of coordinates and states, as produced by `diff`. The states are
+    /// applied as-is: counts of neighboring mines are not updated, but the number of mines and the explosions follow
+    /// the patched states. Nothing is patched if any of the coordinates are outside the minefield
+    pub fn apply_diff(&mut self, diff: &[((Coord, Coord), SpotState)]) -> Result<(), DiffError> {
+        if let Some(((x, y), _state)) = diff.iter().find(|((x, y), _state)| self.index(*x, *y).is_none()) {
+            return Err(DiffError::OutOfBounds { x: *x, y: *y });
+        }
+
+        for &((x, y), state) in diff {
+            let index = self.index(x, y).unwrap();
+            let was_mine = self.get(index).is_mine();
+            self.update(index, |spot| spot.state = state);
+
+            match (was_mine, self.get(index).is_mine()) {
+                (false, true) => self.mines += 1,
+                (true, false) => self.mines -= 1,
+                _ => {},
+            }
+
+            // Keep track of the explosions patched in or out
+            if state == SpotState::ExplodedMine {
+                self.exploded_at = Some((x, y));
+            } else if self.exploded_at == Some((x, y)) {
+                let exploded = self.spots().find(|(_coords, spot)| spot.is_exploded());
+                self.exploded_at = exploded.map(|(coords, _spot)| coords);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the whole state of the minefield, including its options and history, to `restore` it later. The listener
+    /// is not part of the checkpoint
+    pub fn checkpoint(&self) -> Minefield<T>
+    where
+        T: Clone,
+    {
+        self.clone()
+    }
+
+    /// Replace the whole state of the minefield with a `checkpoint` taken from it earlier, keeping the listener.
+    /// Nothing is restored if the checkpoint doesn't have the same dimensions and shape
+    pub fn restore(&mut self, checkpoint: Minefield<T>) -> Result<(), DiffError> {
+        if !self.same_shape(&checkpoint) {
+            return Err(DiffError::ShapeMismatch);
+        }
+
+        // The spots which differ from the checkpoint change when it is restored
+        let dirty = self.dirty.take().map(|mut dirty| {
+            dirty.extend((0..self.field.len()).filter(|&index| self.field[index] != checkpoint.field[index]));
+            dirty
+        });
+
+        let listener = std::mem::replace(&mut self.listener, Listener(None));
+        *self = checkpoint;
+        self.listener = listener;
+        self.dirty = dirty;
+
+        Ok(())
+    }
+
+    /// Flood reveal from the given revealed empty spot with no neighboring mines, the same way as stepping on it does,
+    /// and return the coordinates of the newly revealed spots. This restores the flood after spots were revealed out of
+    /// order, e.g. with `apply_diff`. Nothing is revealed for any other spot
+    pub fn flood_from(&mut self, x: Coord, y: Coord) -> Vec<(Coord, Coord)> {
+        let mut revealed = Vec::new();
+
+        if let Some(index) = self.index(x, y) {
+            self.flood(index, &mut revealed);
+        }
+
+        revealed
+    }
+
+    /// Encode the dimensions, mines and state of every spot of the minefield in a compact binary format: a header with
+    /// the magic bytes `MFLD`, a version byte, and the width, height and mines as little endian `u32`s, followed by one
+    /// byte per coordinate of the field grid, in row-major order. Options and history are not encoded, so minefields
+    /// whose edges wrap around (see `with_wrapping`) decode with inconsistent counts
+    #[allow(clippy::unnecessary_cast)] // `Coord` may already be `u32`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + self.field.len());
+        bytes.extend(Self::MAGIC);
+        bytes.push(Self::FORMAT_VERSION);
+        bytes.extend((self.width as u32).to_le_bytes());
+        bytes.extend((self.height as u32).to_le_bytes());
+        bytes.extend(self.mines.to_le_bytes());
+        bytes.extend(self.field.iter().map(|packed| packed.0));
+        bytes
+    }
+
+    /// A hash of the dimensions, mines and state of every spot of the minefield. Equal minefields always produce the
+    /// same hash, whatever the width of `Coord`, which makes it suitable for comparing the outcome of a replay with a
+    /// claimed result
+    pub fn state_hash(&self) -> u64 {
+        fnv1a(
+            u64::from(self.width).to_le_bytes().into_iter()
+                .chain(u64::from(self.height).to_le_bytes())
+                .chain(self.mines.to_le_bytes())
+                .chain(self.field.iter().map(|packed| packed.0))
+        )
+    }
+
+    /// Place a mine at the given field coordinates, and update neighboring spots. A flagged (or questioned) spot keeps
+    /// its mark.
+    /// Mines can't be placed on revealed spots
+    pub fn place_mine(&mut self, x: Coord, y: Coord) -> Result<(), EditError> {
+        let index = self.index(x, y).ok_or(EditError::OutOfBounds { x, y })?;
+
+        let state = match self.get(index).state {
+            // Only place a mine in an empty spot
+            SpotState::HiddenEmpty { neighboring_mines: _ } => SpotState::HiddenMine,
+            SpotState::FlaggedEmpty { neighboring_mines: _ } => SpotState::FlaggedMine,
+            SpotState::QuestionedEmpty { neighboring_mines: _ } => SpotState::QuestionedMine,
+            SpotState::HiddenMine | SpotState::FlaggedMine | SpotState::QuestionedMine => {
+                return Err(EditError::AlreadyMine { x, y })
+            },
+            SpotState::RevealedEmpty { neighboring_mines: _ } |
+            SpotState::ExplodedMine |
+            SpotState::WrongFlag { neighboring_mines: _ } |
+            SpotState::RevealedMine => {
+                return Err(EditError::Revealed { x, y })
+            },
+        };
+
+        self.update(index, |spot| spot.state = state);
+        self.update_neighbor_counts(x, y, true);
+        self.mines += 1;
+
+        Ok(())
+    }
+
+    /// Remove the mine at the given field coordinates, turning it back into an empty spot, and update neighboring
+    /// spots. A flagged (or questioned) spot keeps its mark. Mines can't be removed from revealed spots
+    pub fn remove_mine(&mut self, x: Coord, y: Coord) -> Result<(), EditError> {
+        let index = self.index(x, y).ok_or(EditError::OutOfBounds { x, y })?;
+        let neighboring_mines = self.neighboring_mines(x, y);
+
+        let state = match self.get(index).state {
+            // Only remove a mine from a mine spot
+            SpotState::HiddenMine => SpotState::HiddenEmpty { neighboring_mines },
+            SpotState::FlaggedMine => SpotState::FlaggedEmpty { neighboring_mines },
+            SpotState::QuestionedMine => SpotState::QuestionedEmpty { neighboring_mines },
+            SpotState::HiddenEmpty { neighboring_mines: _ } |
+            SpotState::FlaggedEmpty { neighboring_mines: _ } |
+            SpotState::QuestionedEmpty { neighboring_mines: _ } => {
+                return Err(EditError::NotAMine { x, y })
+            },
+            SpotState::RevealedEmpty { neighboring_mines: _ } |
+            SpotState::ExplodedMine |
+            SpotState::WrongFlag { neighboring_mines: _ } |
+            SpotState::RevealedMine => {
+                return Err(EditError::Revealed { x, y })
+            },
+        };
+
+        self.update(index, |spot| spot.state = state);
+        self.update_neighbor_counts(x, y, false);
+        self.mines -= 1;
+
+        Ok(())
+    }
+
+    /// The width of the minefield
+    pub fn width(&self) -> Coord {
+        self.width
+    }
+
+    /// The height of the minefield
+    pub fn height(&self) -> Coord {
+        self.height
+    }
+
+    /// Which coordinates of the field grid hold a mine, in row-major order, regardless of the state of the spots. Holes
+    /// hold no mine
+    pub fn mine_layout(&self) -> Vec<bool> {
+        self.field
+            .iter()
+            .map(|&packed| packed != PackedSpot::HOLE && Spot::from(packed).is_mine())
+            .collect()
+    }
+
+    /// Check if the `other` minefield has the same dimensions and shape, and its mines in the same positions, whatever
+    /// the state of the spots (revealed, flagged or hidden)
+    pub fn same_layout<U>(&self, other: &Minefield<U>) -> bool {
+        self.width == other.width && self.height == other.height && self.mine_layout() == other.mine_layout()
+    }
+
+    /// The number of playable spots in the minefield, which is less than `width * height` for shaped fields
+    pub fn cell_count(&self) -> usize {
+        self.cells
+    }
+
+    /// How close the player is to winning, as the share of empty spots which have been revealed: from `0.0` at the
+    /// start, to `1.0` once every empty spot has been revealed. A minefield without empty spots is always at `1.0`
+    pub fn progress(&self) -> f32 {
+        let safe_spots = self.cells as u32 - self.mines;
+
+        if safe_spots == 0 {
+            1.0
+        } else {
+            self.revealed as f32 / safe_spots as f32
+        }
+    }
+
+    /// The number of empty spots which remain to be revealed. Flags don't change it. The game is won when it reaches
+    /// zero, without any mine exploding
+    pub fn safe_spots_remaining(&self) -> u32 {
+        self.cells as u32 - self.mines - self.revealed
+    }
+
+    /// The number of spots which are not revealed yet: hidden, flagged or questioned, mines included
+    pub fn hidden_remaining(&self) -> u32 {
+        self.cells as u32 - self.shown
+    }
+
+    /// The number of flags placed in the minefield (whether correct or not)
+    pub fn flags_placed(&self) -> u32 {
+        self.flags
+    }
+
+    /// The number of mines in the minefield. This is kept up to date by every way of placing or removing mines,
+    /// whether hidden, flagged or revealed
+    pub fn mines(&self) -> u32 {
+        self.mines
+    }
+
+    /// Full knowledge of where the mines are, whatever the player has seen, e.g. to observe a game while training an
+    /// agent to play it. This is a spoiler, which is why it is only available with the `oracle` feature
+    #[cfg(feature = "oracle")]
+    pub fn oracle(&self) -> Oracle<'_, T> {
+        Oracle { minefield: self }
+    }
+
+    /// Count the mines in the minefield by visiting every spot. This agrees with the cheaper `mines()`, except on a
+    /// `player_view`, which hides the mines but not their number
+    pub fn count_mines(&self) -> u32 {
+        self.field.iter().filter(|packed| **packed != PackedSpot::HOLE && Spot::from(**packed).is_mine()).count() as u32
+    }
+
+    /// Row-major index `y * width + x` of the given coordinates, as used by `mine_layout` and `to_grid`, if they are
+    /// within the field grid. Holes have an index too
+    pub fn index_of(&self, x: Coord, y: Coord) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// Coordinates `(x, y)` of the given row-major index, if it is within the field grid. This is the inverse of
+    /// `index_of`
+    pub fn coords_of(&self, index: usize) -> Option<(Coord, Coord)> {
+        (index < self.field.len()).then(|| self.coords(index))
+    }
+
+    /// Get a particular `Spot` in the field
+    pub fn spot(&self, x: Coord, y: Coord) -> Option<Spot> {
+        self.index(x, y).map(|index| self.get(index))
+    }
+
+    /// The data attached to the spot at the given coordinates, or `None` if the coordinates are outside the minefield
+    pub fn cell_data(&self, x: Coord, y: Coord) -> Option<&T> {
+        self.index(x, y).map(|index| &self.data[index])
+    }
+
+    /// The data attached to the spot at the given coordinates, for modification, or `None` if the coordinates are
+    /// outside the minefield
+    pub fn cell_data_mut(&mut self, x: Coord, y: Coord) -> Option<&mut T> {
+        self.index(x, y).map(|index| &mut self.data[index])
+    }
+
+    /// Iterator for all `Spot`s in the field, together with their coordinates `(x, y)`, in row-major order
+    pub fn spots(&self) -> impl Iterator<Item = ((Coord, Coord), Spot)> + '_ {
+        self.indices().map(|index| (self.coords(index), self.get(index)))
+    }
+
+    /// Iterator for all `Spot`s in the field, together with their coordinates `(x, y)`, guaranteed to be in row-major
+    /// order: sorted by `y` first, then by `x`. Holes of shaped minefields are skipped
+    pub fn spots_ordered(&self) -> impl Iterator<Item = ((Coord, Coord), Spot)> + '_ {
+        self.spots()
+    }
+
+    /// Iterator for the `Spot`s inside the rectangle with the given opposite corners (both included), together with
+    /// their coordinates `(x, y)`, in row-major order. The rectangle is clamped to the minefield, and holes are skipped
+    pub fn spots_in_rect(
+        &self,
+        x0: Coord,
+        y0: Coord,
+        x1: Coord,
+        y1: Coord,
+    ) -> impl Iterator<Item = ((Coord, Coord), Spot)> + '_ {
+        let (min_x, max_x) = (x0.min(x1), x0.max(x1).min(self.width - 1));
+        let (min_y, max_y) = (y0.min(y1), y0.max(y1).min(self.height - 1));
+
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter_map(|(x, y)| self.spot(x, y).map(|spot| ((x, y), spot)))
+    }
+
+    /// Like `spots_in_rect`, for the rectangle with the given top-left corner, width and height, e.g. the part of a
+    /// large minefield visible in a scrolled view. The rectangle is clamped to the minefield, and is empty if either
+    /// dimension is zero
+    pub fn spots_in_viewport(
+        &self,
+        x0: Coord,
+        y0: Coord,
+        width: Coord,
+        height: Coord,
+    ) -> impl Iterator<Item = ((Coord, Coord), Spot)> + '_ {
+        // Exclusive ends, which leave the ranges empty for empty or off-field viewports
+        let end_x = x0.saturating_add(width).min(self.width);
+        let end_y = y0.saturating_add(height).min(self.height);
+
+        (y0..end_y)
+            .flat_map(move |y| (x0..end_x).map(move |x| (x, y)))
+            .filter_map(|(x, y)| self.spot(x, y).map(|spot| ((x, y), spot)))
+    }
+
+    /// Iterator for all `Spot`s neighboring the given coordinates, together with their coordinates `(x, y)`
+    pub fn neighbors(&self, x: Coord, y: Coord) -> impl Iterator<Item = ((Coord, Coord), Spot)> + '_ {
+        self.neighbors_coords(x, y)
+            .map(|(nx, ny)| ((nx, ny), self.spot(nx, ny).unwrap()))
+    }
+
+    /// Iterator over the coordinates of the 3x3 block of spots centered on the given coordinates, the center
+    /// included, in row-major order. Coordinates outside the minefield and holes are skipped
+    pub fn block_coords(&self, x: Coord, y: Coord) -> impl Iterator<Item = (Coord, Coord)> {
+        let mut block = [(0, 0); 9];
+        let mut count = 0;
+
+        if self.wrapping && self.index(x, y).is_some() {
+            let (wrapped, wrapped_count) = self.wrapped_block(x, y);
+
+            for &(block_x, block_y) in &wrapped[..wrapped_count] {
+                if self.index(block_x, block_y).is_some() {
+                    block[count] = (block_x, block_y);
+                    count += 1;
+                }
+            }
+
+            return block.into_iter().take(count);
+        }
+
+        for block_y in y.saturating_sub(1)..=y.saturating_add(1) {
+            for block_x in x.saturating_sub(1)..=x.saturating_add(1) {
+                if self.index(block_x, block_y).is_some() {
+                    block[count] = (block_x, block_y);
+                    count += 1;
+                }
+            }
+        }
+
+        block.into_iter().take(count)
+    }
+
+    /// Count the hidden, flagged and revealed neighbors of the given coordinates in a single pass. Returns an empty
+    /// summary if the coordinates are outside the minefield
+    pub fn neighbor_summary(&self, x: Coord, y: Coord) -> NeighborSummary {
+        let mut summary = NeighborSummary::default();
+
+        if self.index(x, y).is_none() {
+            return summary;
+        }
+
+        for (_neighbor_coords, spot) in self.neighbors(x, y) {
+            if spot.is_hidden() {
+                summary.hidden += 1;
+            } else if spot.is_flagged() {
+                summary.flagged += 1;
+            } else if spot.is_revealed() {
+                summary.revealed += 1;
+
+                if spot.is_mine() {
+                    summary.mines_shown += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Count the spots of the minefield in each `SpotState`
+    pub fn state_histogram(&self) -> StateHistogram {
+        let mut histogram = StateHistogram::default();
+
+        for (_spot_coords, spot) in self.spots() {
+            let count = match spot.state {
+                SpotState::HiddenEmpty { neighboring_mines: _ } => &mut histogram.hidden_empty,
+                SpotState::HiddenMine => &mut histogram.hidden_mine,
+                SpotState::FlaggedEmpty { neighboring_mines: _ } => &mut histogram.flagged_empty,
+                SpotState::FlaggedMine => &mut histogram.flagged_mine,
+                SpotState::RevealedEmpty { neighboring_mines: _ } => &mut histogram.revealed_empty,
+                SpotState::ExplodedMine => &mut histogram.exploded_mine,
+                SpotState::WrongFlag { neighboring_mines: _ } => &mut histogram.wrong_flag,
+                SpotState::RevealedMine => &mut histogram.revealed_mine,
+                SpotState::QuestionedEmpty { neighboring_mines: _ } => &mut histogram.questioned_empty,
+                SpotState::QuestionedMine => &mut histogram.questioned_mine,
+            };
+
+            *count += 1;
+        }
+
+        histogram
+    }
+
+    /// Randomly place the given number of mines in the field, using the given random number generator
+    fn place_random_mines(&mut self, mines: u32, rng: &mut impl Rng) {
+        // Add mines to minefield
+
+        // When the mines take less than a quarter of the free spots, picking random spots and retrying the ones which
+        // can't receive a mine takes less than 4/3 tries per mine on average, without allocating anything. This keeps
+        // sparse mines on huge fields cheap. The free spots are counted from the counters, which may overestimate them
+        // in unusual states (e.g. wrong flags shown after a loss), so the tries are bounded, and the mines left over
+        // are placed below
+        let reserved_free = self.reserved.iter().filter(|&&index| self.can_take_mine(index)).count();
+        let free = (self.cells - self.mines as usize - self.revealed as usize).saturating_sub(reserved_free);
+        let mut placed = 0;
+
+        if (mines as usize) < free / 4 {
+            let mut tries_left = 8 * mines as usize + 64;
+
+            while placed < mines && tries_left > 0 {
+                tries_left -= 1;
+                let index = rng.gen_range(0..self.field.len());
+
+                if self.can_take_mine(index) && !self.reserved.contains(&index) {
+                    let (x, y) = self.coords(index);
+                    placed += u32::from(self.place_mine(x, y).is_ok());
+                }
+            }
+
+            if placed == mines {
+                return;
+            }
+        }
+
+        // We could just start randomly picking indices in the field and hope we haven't picked them before, but if a
+        // user desires a field full of mines, then waiting for the last mines to be placed might take a long time
+        // (e.g. if the field is very large).
+        // That's a problem for an immediate GUI.
+        // So, instead, we'll use some memory in order to ensure that the user can step on a mine as soon as humanly
+        // possible. Reserved spots, revealed spots and mines are left out.
+        let mut spots_remaining: Vec<usize> = self
+            .indices()
+            .filter(|&index| self.can_take_mine(index) && !self.reserved.contains(&index))
+            .collect();
+
+        // Limit the max number of mines to the number of available spots
+        let mines = ((mines - placed) as usize).min(spots_remaining.len());
+
+        // Place mines
+        for _ in 0..mines {
+            let index_rm = rng.gen_range(0..spots_remaining.len());
+            let index = spots_remaining.swap_remove(index_rm);
+            let (x, y) = self.coords(index);
+            self.place_mine(x, y).unwrap();
+        }
+    }
+
+    /// Can a mine be placed on the spot at the given field index, i.e. is it an empty spot which is not revealed?
+    fn can_take_mine(&self, index: usize) -> bool {
+        self.field[index] != PackedSpot::HOLE &&
+            matches!(
+                self.get(index).state,
+                SpotState::HiddenEmpty { neighboring_mines: _ } |
+                SpotState::FlaggedEmpty { neighboring_mines: _ } |
+                SpotState::QuestionedEmpty { neighboring_mines: _ }
+            )
+    }
+
+    /// Remove every mine which is not revealed, updating the counts of neighboring mines
+    fn clear_mines(&mut self) {
+        if self.mines == 0 {
+            return;
+        }
+
+        for index in 0..self.field.len() {
+            if self.field[index] != PackedSpot::HOLE && self.get(index).is_mine() && !self.get(index).is_revealed() {
+                let (x, y) = self.coords(index);
+                self.remove_mine(x, y).unwrap();
+            }
+        }
+    }
+
+    /// Randomly place mines in symmetric pairs, using the given random number generator, as described in
+    /// `with_symmetric_mines`
+    fn place_symmetric_mines(&mut self, mines: u32, axis: Symmetry, rng: &mut impl Rng) {
+        // Group spots with their mirror image, leaving out spots whose mirror image is a hole, and reserved spots
+        let mut orbits: Vec<(usize, usize)> = self
+            .indices()
+            .filter_map(|index| {
+                let (x, y) = self.coords(index);
+                let (mirror_x, mirror_y) = axis.mirror(x, y, self.width, self.height);
+                let mirror_index = self.index(mirror_x, mirror_y)?;
+                let reserved = self.reserved.contains(&index) || self.reserved.contains(&mirror_index);
+                (index <= mirror_index && !reserved).then_some((index, mirror_index))
+            })
+            .collect();
+
+        orbits.shuffle(rng);
+
+        let mut remaining = mines;
+
+        // Placing a mine on a spot which is its own mirror image only places one mine
+        for (index, mirror_index) in orbits {
+            let orbit_mines = if index == mirror_index { 1 } else { 2 };
+
+            if orbit_mines <= remaining {
+                let (x, y) = self.coords(index);
+                let (mirror_x, mirror_y) = self.coords(mirror_index);
+
+                if self.place_mine(x, y).is_ok() && index != mirror_index {
+                    self.place_mine(mirror_x, mirror_y).ok();
+                }
+
+                remaining -= orbit_mines;
+            }
+
+            if remaining == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Field indices of the given number of spots where mines can be placed without any two mines being neighbors, or
+    /// `None` if no such spots were found. Random picks are tried first, and then the spots of every other row and
+    /// column, which fit the most mines on a full grid
+    fn spread_mine_spots(&self, mines: u32, rng: &mut impl Rng) -> Option<Vec<usize>> {
+        /// Number of random picks tried before falling back to a lattice
+        const ATTEMPTS: usize = 32;
+
+        let candidates: Vec<usize> = self
+            .indices()
+            .filter(|&index| {
+                !self.reserved.contains(&index) &&
+                    !self.get(index).is_mine() &&
+                    !self.neighbor_indices(index).any(|n_index| self.get(n_index).is_mine())
+            })
+            .collect();
+
+        for attempt in 0..ATTEMPTS + 4 {
+            let mut spots = if attempt < ATTEMPTS {
+                candidates.clone()
+            } else {
+                let offset = (attempt - ATTEMPTS) as Coord;
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|&index| {
+                        let (x, y) = self.coords(index);
+                        x % 2 == offset % 2 && y % 2 == offset / 2
+                    })
+                    .collect()
+            };
+            spots.shuffle(rng);
+
+            let mut blocked = vec![false; self.field.len()];
+            let mut chosen = Vec::new();
+
+            for index in spots {
+                if chosen.len() == mines as usize {
+                    break;
+                }
+
+                if !blocked[index] {
+                    chosen.push(index);
+                    blocked[index] = true;
+                    self.neighbor_indices(index).for_each(|n_index| blocked[n_index] = true);
+                }
+            }
+
+            if chosen.len() == mines as usize {
+                return Some(chosen);
+            }
+        }
+
+        None
+    }
+
+    /// Step on a given spot of the field, recording the coordinates of every spot revealed by the step
+    fn step_spot(&mut self, x: Coord, y: Coord, changed: &mut Vec<(Coord, Coord)>) -> StepResult {
+        if let Some(index) = self.index(x, y) {
+            #[cfg(feature = "adversarial")]
+            if self.exploded_at.is_none() && self.get(index).is_hidden() {
+                // Take the generator out while the mines are rearranged, which borrows the whole minefield
+                if let Some(mut adversary) = self.adversary.take() {
+                    self.arrange_adversarially(index, &mut adversary.rng);
+                    self.adversary = Some(adversary);
+                }
+            }
+
+            if self.move_first_mine && !self.started && self.get(index).is_hidden() && self.get(index).is_mine() {
+                // Relocate the mine, so that the first step is always safe
+                let free_index = self
+                    .indices()
+                    .filter(|i| !self.reserved.contains(i))
+                    .find(|i| matches!(self.get(*i).state, SpotState::HiddenEmpty { neighboring_mines: _ }));
+
+                if let Some(free_index) = free_index {
+                    let (free_x, free_y) = self.coords(free_index);
+                    self.remove_mine(x, y).unwrap();
+                    self.place_mine(free_x, free_y).unwrap();
+                }
+            }
+
+            let step_result = self.update(index, Spot::step);
+
+            if step_result != StepResult::Invalid {
+                self.started = true;
+                changed.push((x, y));
+            }
+
+            if step_result == StepResult::Boom {
+                self.exploded_at = Some((x, y));
+            }
+
+            if self.flood_enabled {
+                self.flood(index, changed);
+            }
+
+            step_result
+        } else {
+            // Step is outside minefield
+            StepResult::Invalid
+        }
+    }
+
+    /// Flood reveal from the spot at the given field index, if it is a revealed empty spot with no neighboring mines:
+    /// reveal its hidden empty neighbors, and continue from those which have no neighboring mines either
+    fn flood(&mut self, index: usize, changed: &mut Vec<(Coord, Coord)>) {
+        if self.get(index).state != (SpotState::RevealedEmpty { neighboring_mines: 0 }) {
+            return;
+        }
+
+        let mut queued = vec![false; self.field.len()];
+        let mut spots_to_visit = VecDeque::from([index]);
+        queued[index] = true;
+
+        while let Some(v_index) = spots_to_visit.pop_front() {
+            for n_index in self.neighbor_indices(v_index) {
+                if queued[n_index] {
+                    continue;
+                }
+
+                if let SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } = self.get(n_index).state {
+                    // Reveal the hidden empty spot by stepping on it
+                    let _step_result = self.update(n_index, Spot::step);
+                    debug_assert_eq!(_step_result, StepResult::Phew);
+                    changed.push(self.coords(n_index));
+                    queued[n_index] = true;
+
+                    if neighboring_mines == 0 {
+                        // contine flood revealing neighbors from this spot
+                        spots_to_visit.push_back(n_index);
+                    }
+                } else if self.flood_through_flags &&
+                    self.get(n_index).state == (SpotState::FlaggedEmpty { neighboring_mines: 0 }) {
+                    // Clear the wrong flag, and continue flood revealing through it
+                    self.update(n_index, |spot| spot.state = SpotState::RevealedEmpty { neighboring_mines: 0 });
+                    changed.push(self.coords(n_index));
+                    queued[n_index] = true;
+                    spots_to_visit.push_back(n_index);
+                }
+            }
+        }
+    }
+
+    /// Rearrange the unknown mines before the player steps on the spot at the given field index, as described in
+    /// `with_adversarial()`
+    #[cfg(feature = "adversarial")]
+    fn arrange_adversarially(&mut self, index: usize, rng: &mut impl Rng) {
+        // If no consistent layout puts a mine here, the spot is provably safe, and so it is in the current layout
+        let Some(mine_layout) = self.consistent_layout(index, true, rng) else {
+            return;
+        };
+
+        let (safe, _mines) = self.certain_spots();
+        let layout = if safe.iter().any(|&s_index| s_index != index && self.get(s_index).is_hidden()) {
+            // The player guessed, although they knew of a safe spot
+            mine_layout
+        } else if let Some(safe_layout) = self.consistent_layout(index, false, rng) {
+            safe_layout
+        } else {
+            // The spot is provably a mine, and so it is in the current layout
+            return;
+        };
+
+        let unknown: Vec<usize> = self.indices().filter(|&u_index| Self::is_unknown(self.get(u_index))).collect();
+
+        // Remove the mines first, so that the mine count never goes above the original one
+        for &u_index in &unknown {
+            if self.get(u_index).is_mine() && !layout.contains(&u_index) {
+                let (x, y) = self.coords(u_index);
+                self.remove_mine(x, y).unwrap();
+            }
+        }
+
+        for &u_index in &unknown {
+            if !self.get(u_index).is_mine() && layout.contains(&u_index) {
+                let (x, y) = self.coords(u_index);
+                self.place_mine(x, y).unwrap();
+            }
+        }
+    }
+
+    /// Field indices of the unknown spots holding a mine in a random layout which is consistent with everything the
+    /// player can see, and where the spot at the given field index holds a mine or not, as requested. `None` if there
+    /// is no such layout, or if the search for one runs out of effort
+    #[cfg(feature = "adversarial")]
+    fn consistent_layout(&self, index: usize, mine: bool, rng: &mut impl Rng) -> Option<HashSet<usize>> {
+        /// Number of assignments tried before giving up
+        const BUDGET: usize = 100_000;
+
+        // Each revealed spot next to unknown spots requires a number of mines among them
+        let mut required = Vec::new();
+        let mut constraints_of: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for c_index in self.indices() {
+            let SpotState::RevealedEmpty { neighboring_mines } = self.get(c_index).state else {
+                continue;
+            };
+
+            let mut mines_left = neighboring_mines as usize;
+            let mut unknown = 0;
+
+            for n_index in self.neighbor_indices(c_index) {
+                let spot = self.get(n_index);
+
+                if spot.is_revealed() && spot.is_mine() {
+                    mines_left = mines_left.saturating_sub(1);
+                } else if Self::is_unknown(spot) {
+                    constraints_of.entry(n_index).or_default().push(required.len());
+                    unknown += 1;
+                }
+            }
+
+            if unknown > 0 {
+                required.push(mines_left);
+            }
+        }
+
+        let shown_mines = self.spots().filter(|(_coords, spot)| spot.is_revealed() && spot.is_mine()).count();
+        let total_mines = (self.mines as usize).saturating_sub(shown_mines);
+
+        // The frontier spots are assigned one by one, starting with the requested spot if it is on the frontier
+        let mut frontier: Vec<usize> = constraints_of.keys().copied().filter(|&f_index| f_index != index).collect();
+        frontier.sort_unstable();
+
+        if constraints_of.contains_key(&index) {
+            frontier.insert(0, index);
+        }
+
+        let mut interior: Vec<usize> = self
+            .indices()
+            .filter(|&i_index| {
+                i_index != index && Self::is_unknown(self.get(i_index)) && !constraints_of.contains_key(&i_index)
+            })
+            .collect();
+        let interior_mine = usize::from(mine && !constraints_of.contains_key(&index));
+
+        // Mines assigned so far, and unassigned spots left, around each revealed spot
+        let mut assigned_mines = vec![0; required.len()];
+        let mut open: Vec<usize> = vec![0; required.len()];
+
+        for constraints in constraints_of.values() {
+            for &constraint in constraints {
+                open[constraint] += 1;
+            }
+        }
+
+        let options: Vec<Vec<bool>> = frontier
+            .iter()
+            .map(|&f_index| {
+                if f_index == index {
+                    vec![mine]
+                } else {
+                    let first: bool = rng.gen();
+                    vec![first, !first]
+                }
+            })
+            .collect();
+        let mut values = vec![false; frontier.len()];
+        let mut tried = vec![0; frontier.len()];
+        let mut frontier_mines = 0;
+        let mut position = 0;
+        let mut budget = BUDGET;
+
+        loop {
+            if position == frontier.len() {
+                let rest = total_mines.checked_sub(frontier_mines + interior_mine);
+                if rest.is_some_and(|rest| rest <= interior.len()) {
+                    break;
+                }
+            } else if tried[position] < options[position].len() {
+                budget = budget.checked_sub(1)?;
+
+                let value = options[position][tried[position]];
+                tried[position] += 1;
+                values[position] = value;
+                frontier_mines += usize::from(value);
+
+                let mut consistent = frontier_mines + interior_mine <= total_mines;
+                for &constraint in &constraints_of[&frontier[position]] {
+                    open[constraint] -= 1;
+                    assigned_mines[constraint] += usize::from(value);
+                    consistent &= assigned_mines[constraint] <= required[constraint] &&
+                        assigned_mines[constraint] + open[constraint] >= required[constraint];
+                }
+
+                position += 1;
+                if consistent {
+                    continue;
+                }
+            } else {
+                tried[position] = 0;
+            }
+
+            // Undo the last assignment, so that its next option gets tried
+            if position == 0 {
+                return None;
+            }
+
+            position -= 1;
+            frontier_mines -= usize::from(values[position]);
+            for &constraint in &constraints_of[&frontier[position]] {
+                open[constraint] += 1;
+                assigned_mines[constraint] -= usize::from(values[position]);
+            }
+        }
+
+        let rest = total_mines - frontier_mines - interior_mine;
+        interior.shuffle(rng);
+
+        let mut layout: HashSet<usize> = frontier
+            .iter()
+            .zip(values)
+            .filter_map(|(&f_index, value)| value.then_some(f_index))
+            .chain(interior.into_iter().take(rest))
+            .collect();
+
+        if interior_mine == 1 {
+            layout.insert(index);
+        }
+
+        Some(layout)
+    }
+
+    /// Automatically step on all hidden neighbors of a revealed spot, recording the coordinates of every spot revealed
+    fn auto_step_spot(&mut self, x: Coord, y: Coord, changed: &mut Vec<(Coord, Coord)>) -> StepResult {
+        if let Some(spot) = self.spot(x, y) {
+            if let SpotState::RevealedEmpty { neighboring_mines } = spot.state {
+                 // count the flags around the given coords, along with the mines already shown, which need no flag
+                 let placed_flags = self
+                    .neighbors_coords(x, y)
+                    .map(|(x, y)| self.spot(x, y).unwrap())
+                    .filter(|spot| spot.is_flagged() || (spot.is_revealed() && spot.is_mine()))
+                    .count() as u8;
+
+                // Only try to autostep if the user has placed enough flags around the spot whose neighbors will be
+                // autorevealed
+                if placed_flags == neighboring_mines {
+                    for (nx, ny) in self.neighbors_coords(x, y) {
+                        if StepResult::Boom == self.step_spot(nx, ny, changed) {
+                            // Eager Boom return
+                            return StepResult::Boom;
+                        }
+                    }
+
+                    StepResult::Phew
+                } else {
+                    // Not enough flags placed by user in order to autostep
+                    StepResult::Invalid
+                }
+            } else {
+                // Spot is not revealed yet
+                StepResult::Invalid
+            }
+        } else {
+            // invalid spot coordinates
+            StepResult::Invalid
+        }
+    }
+
+    /// Apply a smart chord on a revealed spot, recording the coordinates of every spot revealed or flagged
+    fn smart_chord_spot(&mut self, x: Coord, y: Coord, changed: &mut Vec<(Coord, Coord)>) -> StepResult {
+        let Some(SpotState::RevealedEmpty { neighboring_mines }) = self.spot(x, y).map(|spot| spot.state) else {
+            return StepResult::Invalid;
+        };
+
+        if self.game_state() == GameState::Lost {
+            return StepResult::Invalid;
+        }
+
+        let summary = self.neighbor_summary(x, y);
+
+        if summary.flagged + summary.mines_shown == neighboring_mines {
+            return self.auto_step_spot(x, y, changed);
+        }
+
+        let hidden: Vec<usize> = self
+            .neighbor_indices(self.index(x, y).unwrap())
+            .filter(|&n_index| self.get(n_index).is_hidden())
+            .collect();
+
+        if summary.flagged + summary.mines_shown + summary.hidden != neighboring_mines ||
+            (self.limit_flags && self.flags as usize + hidden.len() > self.mines as usize) {
+            return StepResult::Invalid;
+        }
+
+        for n_index in hidden {
+            self.update(n_index, |spot| {
+                spot.state = match spot.state {
+                    SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } => SpotState::FlaggedEmpty { neighboring_mines },
+                    SpotState::HiddenMine | SpotState::QuestionedMine => SpotState::FlaggedMine,
+                    state => state,
+                }
+            });
+            changed.push(self.coords(n_index));
+        }
+
+        StepResult::Phew
+    }
+
+    /// Toggle the flag of a given spot, recording its coordinates if a flag was added or removed
+    fn toggle_flag_spot(&mut self, x: Coord, y: Coord, changed: &mut Vec<(Coord, Coord)>) -> FlagToggleResult {
+        if let Some(index) = self.index(x, y) {
+            let spot = self.get(index);
+
+            if self.limit_flags && spot.is_hidden() && !spot.is_questioned() && self.flags >= self.mines {
+                // No more flags can be added
+                return FlagToggleResult::LimitReached;
+            }
+
+            let flag_mode = self.flag_mode;
+            let flag_result = self.update(index, |spot| spot.flag(flag_mode));
+
+            if flag_result != FlagToggleResult::None {
+                changed.push((x, y));
+
+                let all_flagged = self.flags == self.mines && self.wrong_flags == 0;
+                if self.auto_finish && all_flagged && self.game_state() == GameState::InProgress {
+                    // The spot just unflagged may be revealed too, but is only reported once
+                    let mut revealed = Vec::new();
+                    self.step_all_hidden_empty(&mut revealed);
+                    changed.extend(revealed.into_iter().filter(|&coords| coords != (x, y)));
+                }
+            }
+
+            flag_result
+        } else {
+            // invalid coordinates, no flag was added or removed
+            FlagToggleResult::None
+        }
+    }
+
+    /// Start or stop the timer, which runs while the game is in progress, once started, unless paused
+    fn update_timer(&mut self) {
+        if self.timer.is_none() {
+            return;
+        }
+
+        let playing = self.started && self.game_state() == GameState::InProgress;
+
+        if let Some(timer) = &mut self.timer {
+            let now = timer.clock.now();
+
+            match timer.running_since {
+                None if playing && !timer.paused => timer.running_since = Some(now),
+                Some(since) if !playing || timer.paused => {
+                    timer.elapsed += now.saturating_sub(since);
+                    timer.running_since = None;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Step on every hidden empty spot left, i.e. finish the game once all the mines are known
+    fn step_all_hidden_empty(&mut self, changed: &mut Vec<(Coord, Coord)>) {
+        for index in 0..self.field.len() {
+            let spot = self.get(index);
+
+            // Earlier steps may have flooded this spot already
+            if self.field[index] != PackedSpot::HOLE && spot.is_hidden() && !spot.is_mine() {
+                let (x, y) = self.coords(index);
+                self.step_spot(x, y, changed);
+            }
+        }
+    }
+
+    /// Group all spots whose field index `in_region` returns `true` for into regions of connected neighbors
+    fn connected_regions(&self, in_region: impl Fn(usize) -> bool) -> Vec<Vec<(Coord, Coord)>> {
+        let mut visited = vec![false; self.field.len()];
+        let mut regions = Vec::new();
+
+        for index in self.indices() {
+            if visited[index] || !in_region(index) {
+                continue;
+            }
+
+            // Collect the region by flooding from its first spot
+            visited[index] = true;
+            let mut region = vec![self.coords(index)];
+            let mut spots_to_visit = vec![self.coords(index)];
+
+            while let Some((x, y)) = spots_to_visit.pop() {
+                for (nx, ny) in self.neighbors_coords(x, y) {
+                    let n_index = self.index(nx, ny).unwrap();
+
+                    if !visited[n_index] && in_region(n_index) {
+                        visited[n_index] = true;
+                        region.push((nx, ny));
+                        spots_to_visit.push((nx, ny));
+                    }
+                }
+            }
+
+            regions.push(region);
+        }
+
+        regions
+    }
+
+    /// All openings: the regions of connected empty spots with no neighboring mines, each together with the spots
+    /// around it, i.e. every spot which a single step into the opening reveals
+    fn openings(&self) -> Vec<Vec<(Coord, Coord)>> {
+        let zeroes = self.connected_regions(|index| self.get(index).neighboring_mines() == Some(0));
+
+        zeroes
+            .into_iter()
+            .map(|zero_region| {
+                let mut opening: Vec<(Coord, Coord)> = zero_region.clone();
+                let mut in_opening: HashSet<(Coord, Coord)> = zero_region.iter().copied().collect();
+
+                for &(x, y) in &zero_region {
+                    for neighbor in self.neighbors_coords(x, y) {
+                        if in_opening.insert(neighbor) {
+                            opening.push(neighbor);
+                        }
+                    }
+                }
+
+                opening
+            })
+            .collect()
+    }
+
+    /// A copy of the minefield with the same shape and mines, where every spot is hidden
+    fn hidden_copy(&self) -> Minefield {
+        let mut game = Minefield::with_shape(self.width, self.height, |x, y| self.spot(x, y).is_some());
+        for ((x, y), _spot) in self.spots().filter(|(_coords, spot)| spot.is_mine()) {
+            game.place_mine(x, y).unwrap();
+        }
+
+        game
+    }
+
+    /// Clear the minefield by stepping on every spot `certain_spots` proves safe, and on the empty spot least likely to
+    /// hold a mine whenever nothing is provably safe, and count those guesses
+    fn play_out_guessing(&mut self) -> u32 {
+        let mut guesses = 0;
+
+        while self.game_state() == GameState::InProgress {
+            let (safe, _mines) = self.certain_spots();
+            let mut safe: Vec<usize> = safe.into_iter().filter(|&index| self.get(index).is_hidden()).collect();
+
+            if safe.is_empty() {
+                guesses += 1;
+
+                let guess = self
+                    .mine_likelihoods()
+                    .into_iter()
+                    .filter(|&(index, _likelihood)| self.get(index).is_hidden() && !self.get(index).is_mine())
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+                match guess {
+                    Some((index, _likelihood)) => safe.push(index),
+                    None => break,
+                }
+            }
+
+            for index in safe {
+                let (x, y) = self.coords(index);
+                self.step(x, y);
+            }
+        }
+
+        guesses
+    }
+
+    /// The state of the spot at the given field index as seen by the player: hidden, flagged and questioned mines look
+    /// like empty spots with their true count of neighboring mines
+    fn visible_state(&self, index: usize) -> SpotState {
+        let (x, y) = self.coords(index);
+
+        match self.get(index).state {
+            SpotState::HiddenMine => SpotState::HiddenEmpty { neighboring_mines: self.neighboring_mines(x, y) },
+            SpotState::FlaggedMine => SpotState::FlaggedEmpty { neighboring_mines: self.neighboring_mines(x, y) },
+            SpotState::QuestionedMine => SpotState::QuestionedEmpty { neighboring_mines: self.neighboring_mines(x, y) },
+            state => state,
+        }
+    }
+
+    /// A row-major grid of the given state of every spot, with `None` for holes
+    fn grid_with(&self, state: impl Fn(usize) -> SpotState) -> Vec<Vec<Option<SpotState>>> {
+        let width = self.width as usize;
+
+        (0..self.field.len())
+            .map(|index| (self.field[index] != PackedSpot::HOLE).then(|| state(index)))
+            .collect::<Vec<_>>()
+            .chunks(width)
+            .map(<[_]>::to_vec)
+            .collect()
+    }
+
+    /// Check if the `other` minefield has the same dimensions and holes
+    fn same_shape(&self, other: &Minefield<T>) -> bool {
+        self.width == other.width &&
+            self.height == other.height &&
+            self.field
+                .iter()
+                .zip(other.field.iter())
+                .all(|(a, b)| (*a == PackedSpot::HOLE) == (*b == PackedSpot::HOLE))
+    }
+
+    /// Add one to (or remove one from) the count of neighboring mines of every empty spot around the given coordinates
+    fn update_neighbor_counts(&mut self, x: Coord, y: Coord, mine_added: bool) {
+        for (nx, ny) in self.neighbors_coords(x, y) {
+            let n_index = self.index(nx, ny).unwrap();
+
+            self.update(n_index, |spot| {
+                match &mut spot.state {
+                    SpotState::HiddenEmpty { neighboring_mines } |
+                    SpotState::FlaggedEmpty { neighboring_mines } |
+                    SpotState::RevealedEmpty { neighboring_mines } |
+                    SpotState::WrongFlag { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } => {
+                        if mine_added {
+                            *neighboring_mines += 1;
+                        } else {
+                            *neighboring_mines -= 1;
+                        }
+                    },
+                    _ => {},
+                }
+            });
+        }
+    }
+
+    /// Iterator over the coordinates of all neighbors in a range of 1 unit, relative to the given coordiantes
+    fn neighbors_coords(&self, x: Coord, y: Coord) -> impl Iterator<Item = (Coord, Coord)>
+    {
+        let min_x = x.saturating_sub(1);
+        let max_x = x.saturating_add(1);
+
+        let min_y = y.saturating_sub(1);
+        let max_y = y.saturating_add(1);
+
+        // Collect the neighbors up front, so that the iterator doesn't borrow the minefield while it's being mutated
+        let mut neighbors = [(0, 0); 8];
+        let mut count = 0;
+
+        if self.wrapping && x < self.width && y < self.height {
+            let (block, block_count) = self.wrapped_block(x, y);
+
+            for &(neighbor_x, neighbor_y) in &block[..block_count] {
+                if self.index(neighbor_x, neighbor_y).is_some() && (neighbor_x, neighbor_y) != (x, y) {
+                    neighbors[count] = (neighbor_x, neighbor_y);
+                    count += 1;
+                }
+            }
+
+            return neighbors.into_iter().take(count);
+        }
+
+        for (neighbor_x, neighbor_y) in (min_x..=max_x).flat_map(move |i| (min_y..=max_y).map(move |j| (i, j))) {
+            // the neighbor coords are within the minefield grid, and not a hole
+            if self.index(neighbor_x, neighbor_y).is_some() &&
+                // the neighbor coords are not same as `self`
+                !(neighbor_x == x && neighbor_y == y) {
+                neighbors[count] = (neighbor_x, neighbor_y);
+                count += 1;
+            }
+        }
+
+        neighbors.into_iter().take(count)
+    }
+
+    /// Iterator over the field indices of all neighbors of the given field index, skipping holes
+    fn neighbor_indices(&self, index: usize) -> impl Iterator<Item = usize> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let (x, y) = (index % width, index / width);
+
+        let mut neighbors = [0; 8];
+        let mut count = 0;
+
+        if self.wrapping {
+            for (nx, ny) in self.neighbors_coords(x as Coord, y as Coord) {
+                neighbors[count] = self.index(nx, ny).unwrap();
+                count += 1;
+            }
+
+            return neighbors.into_iter().take(count);
+        }
+
+        for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                let n_index = ny * width + nx;
+
+                if n_index != index && self.field[n_index] != PackedSpot::HOLE {
+                    neighbors[count] = n_index;
+                    count += 1;
+                }
+            }
+        }
+
+        neighbors.into_iter().take(count)
+    }
+
+    /// The distinct coordinates of the 3x3 block centered on the given coordinates, wrapped around the edges of the
+    /// field grid, in row-major order (starting from the row above), and how many there are. Holes are included
+    fn wrapped_block(&self, x: Coord, y: Coord) -> ([(Coord, Coord); 9], usize) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut block = [(0, 0); 9];
+        let mut count = 0;
+
+        for dy in [height - 1, 0, 1] {
+            for dx in [width - 1, 0, 1] {
+                let coords = (((x as usize + dx) % width) as Coord, ((y as usize + dy) % height) as Coord);
+
+                // Narrow field grids wrap onto the same coordinates more than once
+                if !block[..count].contains(&coords) {
+                    block[count] = coords;
+                    count += 1;
+                }
+            }
+        }
+
+        (block, count)
+    }
+
+    /// Could this spot hold a mine, as far as the player can tell?
+    fn is_unknown(spot: Spot) -> bool {
+        spot.is_hidden() ||
+            matches!(spot.state, SpotState::FlaggedEmpty { neighboring_mines: _ } | SpotState::FlaggedMine)
+    }
+
+    /// Field indices of the unknown spots which are certainly safe, and of those which certainly hold a mine, deduced
+    /// from the count of each revealed spot on its own, and from pairs of revealed spots where the unknown neighbors of
+    /// one are a subset of the unknown neighbors of the other
+    fn certain_spots(&self) -> (HashSet<usize>, HashSet<usize>) {
+        let proofs = self.proofs();
+        let safe = proofs.iter().filter(|(_index, proof)| !proof.mine).map(|(&index, _proof)| index).collect();
+        let mines = proofs.iter().filter(|(_index, proof)| proof.mine).map(|(&index, _proof)| index).collect();
+
+        (safe, mines)
+    }
+
+    /// The deductions of `certain_spots`, with the proof of each decided spot, by field index
+    fn proofs(&self) -> HashMap<usize, Proof> {
+        /// Decide all `spots` as safe or as mines, proven by `constraints` in the given `pass`, if `mines_left` allows
+        /// only one of the two, returning `true` if anything new was learned
+        fn settle(
+            spots: Vec<usize>,
+            mines_left: usize,
+            pass: usize,
+            constraints: &[usize],
+            proofs: &mut HashMap<usize, Proof>,
+        ) -> bool {
+            let mine = if mines_left == 0 {
+                false
+            } else if mines_left == spots.len() {
+                true
+            } else {
+                return false;
+            };
+
+            spots.into_iter().fold(false, |progress, index| {
+                let new = !proofs.contains_key(&index);
+                if new {
+                    proofs.insert(index, Proof { mine, pass, constraints: constraints.to_vec() });
+                }
+
+                new | progress
+            })
+        }
+
+        let mut proofs: HashMap<usize, Proof> = HashMap::new();
+        let mut progress = true;
+        let mut pass = 0;
+
+        while progress {
+            progress = false;
+
+            // For each revealed spot next to undecided spots: those spots, and how many mines are still among them
+            let constraints: HashMap<usize, (Vec<usize>, usize)> = self
+                .indices()
+                .filter_map(|index| {
+                    let SpotState::RevealedEmpty { neighboring_mines } = self.get(index).state else {
+                        return None;
+                    };
+
+                    let mut remaining = neighboring_mines as usize;
+                    let mut undecided = Vec::new();
+
+                    for n_index in self.neighbor_indices(index) {
+                        let spot = self.get(n_index);
+                        let proof = proofs.get(&n_index);
+
+                        if (spot.is_revealed() && spot.is_mine()) || proof.is_some_and(|proof| proof.mine) {
+                            remaining = remaining.saturating_sub(1);
+                        } else if Self::is_unknown(spot) && proof.is_none() {
+                            undecided.push(n_index);
+                        }
+                    }
+
+                    (!undecided.is_empty()).then_some((index, (undecided, remaining)))
+                })
+                .collect();
+
+            // Visit the constraints in row-major order, so that the proofs don't depend on the order of the map
+            let mut order: Vec<usize> = constraints.keys().copied().collect();
+            order.sort_unstable();
+
+            for index in order {
+                let (undecided, remaining) = &constraints[&index];
+                progress |= settle(undecided.clone(), *remaining, pass, &[index], &mut proofs);
+
+                // Only revealed spots sharing an undecided neighbor can hold a superset of these undecided spots
+                for &n_index in undecided {
+                    for other in self.neighbor_indices(n_index).filter(|&other| other != index) {
+                        let Some((other_undecided, other_remaining)) = constraints.get(&other) else {
+                            continue;
+                        };
+
+                        if !undecided.iter().all(|spot_index| other_undecided.contains(spot_index)) {
+                            continue;
+                        }
+
+                        let Some(mines_left) = other_remaining.checked_sub(*remaining) else {
+                            continue;
+                        };
+
+                        let difference: Vec<usize> = other_undecided
+                            .iter()
+                            .filter(|spot_index| !undecided.contains(spot_index))
+                            .copied()
+                            .collect();
+
+                        if !difference.is_empty() {
+                            progress |= settle(difference, mines_left, pass, &[index, other], &mut proofs);
+                        }
+                    }
+                }
+            }
+
+            pass += 1;
+        }
+
+        proofs
+    }
+
+    /// Rough likelihood of each unknown spot holding a mine: the highest share of missing mines among the constraints
+    /// of the revealed neighbors, or the overall share of missing mines for spots with no revealed neighbor
+    fn mine_likelihoods(&self) -> Vec<(usize, f64)> {
+        let unknown: Vec<usize> = self.indices().filter(|&index| Self::is_unknown(self.get(index))).collect();
+        let shown_mines = self.spots().filter(|(_coords, spot)| spot.is_revealed() && spot.is_mine()).count();
+        let density = self.mines.saturating_sub(shown_mines as u32) as f64 / unknown.len().max(1) as f64;
+
+        unknown
+            .into_iter()
+            .map(|index| {
+                let likelihood = self
+                    .neighbor_indices(index)
+                    .filter_map(|n_index| {
+                        let SpotState::RevealedEmpty { neighboring_mines } = self.get(n_index).state else {
+                            return None;
+                        };
+
+                        let mut missing = neighboring_mines as f64;
+                        let mut candidates = 0.0;
+
+                        for nn_index in self.neighbor_indices(n_index) {
+                            let spot = self.get(nn_index);
+
+                            if spot.is_revealed() && spot.is_mine() {
+                                missing -= 1.0;
+                            } else if Self::is_unknown(spot) {
+                                candidates += 1.0;
+                            }
+                        }
+
+                        Some(missing.max(0.0) / candidates)
+                    })
+                    .fold(None, |max: Option<f64>, likelihood| Some(max.map_or(likelihood, |max| max.max(likelihood))))
+                    .unwrap_or(density);
+
+                (index, likelihood)
+            })
+            .collect()
+    }
+
+    /// Count the mines around the given coordinates
+    fn neighboring_mines(&self, x: Coord, y: Coord) -> u8 {
+        self.neighbors_coords(x, y)
+            .filter(|(nx, ny)| self.spot(*nx, *ny).unwrap().is_mine())
+            .count() as u8
+    }
+
+    /// Index in the field buffer of the spot at the given coordinates, if they are within the field grid and not a hole
+    fn index(&self, x: Coord, y: Coord) -> Option<usize> {
+        if x < self.width && y < self.height {
+            let index = y as usize * self.width as usize + x as usize;
+            (self.field[index] != PackedSpot::HOLE).then_some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Iterator over the indices of all spots in the field buffer, skipping holes
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.field.len()).filter(|index| self.field[*index] != PackedSpot::HOLE)
+    }
+
+    /// Coordinates `(x, y)` of the spot at the given index in the field buffer
+    fn coords(&self, index: usize) -> (Coord, Coord) {
+        let x = (index % self.width as usize) as Coord;
+        let y = (index / self.width as usize) as Coord;
+        (x, y)
+    }
+
+    /// Decode the spot at the given index in the field buffer
+    fn get(&self, index: usize) -> Spot {
+        Spot::from(self.field[index])
+    }
+
+    /// Decode the spot at the given index in the field buffer, modify it, and store it back
+    fn update<R>(&mut self, index: usize, f: impl FnOnce(&mut Spot) -> R) -> R {
+        let old_spot = self.get(index);
+        let mut spot = old_spot;
+        let result = f(&mut spot);
+        let packed = PackedSpot::from(spot);
+
+        if let Some(dirty) = &mut self.dirty {
+            if self.field[index] != packed {
+                dirty.insert(index);
+            }
+        }
+
+        self.field[index] = packed;
+
+        // Keep the counters up to date
+        match (old_spot.is_flagged(), spot.is_flagged()) {
+            (false, true) => self.flags += 1,
+            (true, false) => self.flags -= 1,
+            _ => {},
+        }
+
+        let is_wrong_flag = |spot: Spot| matches!(spot.state, SpotState::FlaggedEmpty { neighboring_mines: _ });
+
+        match (is_wrong_flag(old_spot), is_wrong_flag(spot)) {
+            (false, true) => self.wrong_flags += 1,
+            (true, false) => self.wrong_flags -= 1,
+            _ => {},
+        }
+
+        let is_revealed_empty = |spot: Spot| matches!(spot.state, SpotState::RevealedEmpty { neighboring_mines: _ });
+
+        match (is_revealed_empty(old_spot), is_revealed_empty(spot)) {
+            (false, true) => self.revealed += 1,
+            (true, false) => self.revealed -= 1,
+            _ => {},
+        }
+
+        match (old_spot.is_revealed(), spot.is_revealed()) {
+            (false, true) => self.shown += 1,
+            (true, false) => self.shown -= 1,
+            _ => {},
+        }
+
+        result
+    }
+}
+
+/// Minefields are equal when they have the same dimensions, shape and number of mines, and the spot at every
+/// coordinate has the same state. This doesn't depend on how the spots are stored or iterated. The data attached to
+/// the spots, the options and the history are not compared
+impl<T> PartialEq for Minefield<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width &&
+            self.height == other.height &&
+            self.mines == other.mines &&
+            self.field == other.field
+    }
+}
+
+impl<T> Eq for Minefield<T> {}
+
+/// Hashes the dimensions, the number of mines and the state of every spot in row-major order, consistently with
+/// `PartialEq`
+impl<T> std::hash::Hash for Minefield<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.mines.hash(state);
+        self.field.hash(state);
+    }
+}
+
+/// State of the spot in a minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SpotState {
+    /// This spot has not been visited
+    HiddenEmpty{neighboring_mines: u8},
+
+    /// This is a hidden mine
+    HiddenMine,
+
+    /// This spot is empty, but was flagged as a mine
+    FlaggedEmpty{neighboring_mines: u8},
+
+    /// This spot contains a mine which was correctly flagged
+    FlaggedMine,
+
+    /// This spot is empty and was correctly revealed
+    RevealedEmpty{neighboring_mines: u8},
+
+    /// This spot contains a mine and was incorrectly revealed
+    ExplodedMine,
+
+    /// This spot is empty, and its flag was shown to be wrong after the game was lost
+    WrongFlag{neighboring_mines: u8},
+
+    /// This spot contains a mine which was never found, shown after the game was lost
+    RevealedMine,
+
+    /// This spot is empty, and was marked with a question mark
+    QuestionedEmpty{neighboring_mines: u8},
+
+    /// This spot contains a mine, and was marked with a question mark
+    QuestionedMine,
+}
+
+/// Spot struct describing the characteristics of the minefield at a particular position
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Spot {
+    /// State of the spot, which only the minefield changes, so that it stays consistent with its neighbors
+    state: SpotState,
+}
+
+impl Spot {
+    /// The state of this spot
+    pub fn state(&self) -> SpotState {
+        self.state
+    }
+
+    /// Step on this spot, if possible
+    fn step(&mut self) -> StepResult {
+        match self.state {
+            SpotState::HiddenEmpty { neighboring_mines } | SpotState::QuestionedEmpty { neighboring_mines } => {
+                self.state = SpotState::RevealedEmpty { neighboring_mines };
+                StepResult::Phew
+            },
+            SpotState::HiddenMine | SpotState::QuestionedMine => {
+                self.state = SpotState::ExplodedMine;
+                StepResult::Boom
+            },
+            _ => {
+                StepResult::Invalid
+            }
+        }
+    }
+
+    /// Cycle through the marks of this spot allowed by the flag mode, if possible
+    fn flag(&mut self, flag_mode: FlagMode) -> FlagToggleResult {
+        match (self.state, flag_mode) {
+            (SpotState::HiddenEmpty { neighboring_mines }, _) => {
+                self.state = SpotState::FlaggedEmpty { neighboring_mines };
+                FlagToggleResult::Added
+            },
+            (SpotState::HiddenMine, _) => {
+                self.state = SpotState::FlaggedMine;
+                FlagToggleResult::Added
+            },
+            (SpotState::FlaggedEmpty { neighboring_mines }, FlagMode::Flags) |
+            (SpotState::QuestionedEmpty { neighboring_mines }, _) => {
+                self.state = SpotState::HiddenEmpty { neighboring_mines };
+                FlagToggleResult::Removed
+            },
+            (SpotState::FlaggedMine, FlagMode::Flags) | (SpotState::QuestionedMine, _) => {
+                self.state = SpotState::HiddenMine;
+                FlagToggleResult::Removed
+            },
+            (SpotState::FlaggedEmpty { neighboring_mines }, FlagMode::FlagsAndQuestionMarks) => {
+                self.state = SpotState::QuestionedEmpty { neighboring_mines };
+                FlagToggleResult::Questioned
+            },
+            (SpotState::FlaggedMine, FlagMode::FlagsAndQuestionMarks) => {
+                self.state = SpotState::QuestionedMine;
+                FlagToggleResult::Questioned
+            },
+            _ => {
+                FlagToggleResult::None
+            }
+        }
+    }
+
+    /// Has this spot been cleared (either correctly flagged or correctly revealed)?
+    pub fn is_resolved(&self) -> bool {
+        matches!(
+            self.state, 
+            SpotState::FlaggedMine | SpotState::RevealedEmpty { neighboring_mines: _ }
+        )
+    }
+
+    /// Does this spot hold a mine (whether hidden, flagged, questioned, exploded or shown after a loss)?
+    pub fn is_mine(&self) -> bool {
+        matches!(
+            self.state,
+            SpotState::HiddenMine |
+            SpotState::FlaggedMine |
+            SpotState::ExplodedMine |
+            SpotState::RevealedMine |
+            SpotState::QuestionedMine
+        )
+    }
+
+    /// Does this spot carry a flag (whether correct or not)?
+    pub fn is_flagged(&self) -> bool {
+        matches!(
+            self.state,
+            SpotState::FlaggedEmpty { neighboring_mines: _ } |
+            SpotState::FlaggedMine |
+            SpotState::WrongFlag { neighboring_mines: _ }
+        )
+    }
+
+    /// Is this spot visible to the player (either a revealed empty spot, an exploded mine, or a mine shown after a
+    /// loss)?
+    pub fn is_revealed(&self) -> bool {
+        matches!(
+            self.state,
+            SpotState::RevealedEmpty { neighboring_mines: _ } | SpotState::ExplodedMine | SpotState::RevealedMine
+        )
+    }
+
+    /// Is this spot still hidden, without a flag (but possibly with a question mark)?
+    pub fn is_hidden(&self) -> bool {
+        matches!(
+            self.state,
+            SpotState::HiddenEmpty { neighboring_mines: _ } |
+            SpotState::HiddenMine |
+            SpotState::QuestionedEmpty { neighboring_mines: _ } |
+            SpotState::QuestionedMine
+        )
+    }
+
+    /// Is this spot marked with a question mark?
+    pub fn is_questioned(&self) -> bool {
+        matches!(self.state, SpotState::QuestionedEmpty { neighboring_mines: _ } | SpotState::QuestionedMine)
+    }
+
+    /// Is this spot a mine which was stepped on?
+    pub fn is_exploded(&self) -> bool {
+        matches!(self.state, SpotState::ExplodedMine)
+    }
+
+    /// The count of mines around this spot, or `None` if this spot holds a mine
+    pub fn neighboring_mines(&self) -> Option<u8> {
+        match self.state {
+            SpotState::HiddenEmpty { neighboring_mines } |
+            SpotState::FlaggedEmpty { neighboring_mines } |
+            SpotState::RevealedEmpty { neighboring_mines } |
+            SpotState::WrongFlag { neighboring_mines } |
+            SpotState::QuestionedEmpty { neighboring_mines } => Some(neighboring_mines),
+            SpotState::HiddenMine |
+            SpotState::FlaggedMine |
+            SpotState::ExplodedMine |
+            SpotState::RevealedMine |
+            SpotState::QuestionedMine => None,
+        }
+    }
+
+    /// The character showing this spot to the player, as used by `Minefield::render`: `.` for hidden, `F` for flagged,
+    /// `?` for questioned, the count (or a space for zero) for revealed empty, `X` for exploded, `*` for a revealed
+    /// mine and `x` for a wrong flag
+    pub fn glyph(&self) -> char {
+        self.glyph_in(&RenderCharset::ASCII)
+    }
+
+    /// The character showing this spot to the player in the given character set
+    pub fn glyph_in(&self, charset: &RenderCharset) -> char {
+        match self.state {
+            SpotState::HiddenEmpty { neighboring_mines: _ } | SpotState::HiddenMine => charset.hidden,
+            SpotState::FlaggedEmpty { neighboring_mines: _ } | SpotState::FlaggedMine => charset.flag,
+            SpotState::QuestionedEmpty { neighboring_mines: _ } | SpotState::QuestionedMine => charset.question,
+            SpotState::RevealedEmpty { neighboring_mines: 0 } => charset.zero,
+            SpotState::RevealedEmpty { neighboring_mines } => (charset.digits)(neighboring_mines),
+            SpotState::ExplodedMine => charset.exploded,
+            SpotState::RevealedMine => charset.mine,
+            SpotState::WrongFlag { neighboring_mines: _ } => charset.wrong_flag,
+        }
+    }
+
+    /// ANSI escape code setting the color of the glyph of this spot, in the classic minesweeper colors, or `None` if
+    /// the glyph keeps the default color
+    #[cfg(feature = "ansi")]
+    fn ansi_color(&self) -> Option<&'static str> {
+        match self.state {
+            SpotState::RevealedEmpty { neighboring_mines } => match neighboring_mines {
+                1 => Some("\x1b[94m"),
+                2 => Some("\x1b[32m"),
+                3 => Some("\x1b[91m"),
+                4 => Some("\x1b[34m"),
+                5 => Some("\x1b[31m"),
+                6 => Some("\x1b[36m"),
+                7 => Some("\x1b[35m"),
+                8 => Some("\x1b[90m"),
+                _ => None,
+            },
+            SpotState::FlaggedEmpty { neighboring_mines: _ } | SpotState::FlaggedMine => Some("\x1b[91m"),
+            SpotState::ExplodedMine | SpotState::WrongFlag { neighboring_mines: _ } => Some("\x1b[1;31m"),
+            SpotState::RevealedMine => Some("\x1b[1m"),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Spot {
+    fn default() -> Self {
+        Self { state: SpotState::HiddenEmpty { neighboring_mines: 0 } }
+    }
+}
+
+/// Characters showing the spots of a minefield as text, for `Minefield::render_charset` and `Spot::glyph_in`
+#[derive(Copy, Clone, Debug)]
+pub struct RenderCharset {
+    /// Hidden spots, without a mark
+    pub hidden: char,
+
+    /// Flagged spots
+    pub flag: char,
+
+    /// Questioned spots
+    pub question: char,
+
+    /// Revealed mines
+    pub mine: char,
+
+    /// Exploded mines
+    pub exploded: char,
+
+    /// Flags shown to be wrong
+    pub wrong_flag: char,
+
+    /// Revealed empty spots without neighboring mines
+    pub zero: char,
+
+    /// Revealed empty spots with 1 to 8 neighboring mines, given their count
+    pub digits: fn(u8) -> char,
+
+    /// Coordinates of the field grid without a spot
+    pub hole: char,
+}
+
+impl RenderCharset {
+    /// Plain ASCII characters, which every terminal and log can show. This is the character set of `Minefield::render`
+    pub const ASCII: RenderCharset = RenderCharset {
+        hidden: '.',
+        flag: 'F',
+        question: '?',
+        mine: '*',
+        exploded: 'X',
+        wrong_flag: 'x',
+        zero: ' ',
+        digits: decimal_digit,
+        hole: '#',
+    };
+
+    /// Unicode symbols, like the ones in the board diagrams of this crate
+    pub const UNICODE: RenderCharset = RenderCharset {
+        hidden: '•',
+        flag: '⚐',
+        question: '?',
+        mine: '☢',
+        exploded: '✸',
+        wrong_flag: '✗',
+        zero: ' ',
+        digits: decimal_digit,
+        hole: ' ',
+    };
+}
+
+impl Default for RenderCharset {
+    fn default() -> Self {
+        RenderCharset::ASCII
+    }
+}
+
+/// The decimal digit of a count of neighboring mines
+fn decimal_digit(count: u8) -> char {
+    char::from(b'0' + count)
+}
+
+/// How `Minefield::proofs` decided an unknown spot
+struct Proof {
+    /// Whether the spot holds a mine, or is safe
+    mine: bool,
+
+    /// Pass of the deductions in which the spot was decided. Only the spots decided in earlier passes are known to the
+    /// constraints of a pass
+    pass: usize,
+
+    /// Field indices of the one or two revealed spots whose counts decided the spot
+    constraints: Vec<usize>,
+}
+
+/// Backtracking search over the mine assignments of unknown spots, constrained by revealed counts
+struct Search {
+    /// Number of mines still needed by each constraint
+    needed: Vec<i32>,
+
+    /// Number of unassigned unknown spots around each constraint
+    unassigned: Vec<i32>,
+
+    /// Constraints touching each unknown spot
+    touching: Vec<Vec<usize>>,
+}
+
+impl Search {
+    /// Assign the unknown spots from index `next` onwards, calling `found` for every complete and consistent assignment
+    /// until it returns `true`. Returns `true` if the search was stopped that way
+    fn search(
+        &mut self,
+        next: usize,
+        mines_left: i32,
+        assignment: &mut Vec<bool>,
+        found: &mut impl FnMut(&[bool]) -> bool,
+    ) -> bool {
+        let spots_left = (assignment.len() - next) as i32;
+
+        if mines_left < 0 || mines_left > spots_left {
+            return false;
+        }
+
+        if next == assignment.len() {
+            return self.needed.iter().all(|needed| *needed == 0) && found(assignment);
+        }
+
+        for is_mine in [true, false] {
+            assignment[next] = is_mine;
+
+            let mut consistent = true;
+            for t in 0..self.touching[next].len() {
+                let c = self.touching[next][t];
+                self.unassigned[c] -= 1;
+                if is_mine {
+                    self.needed[c] -= 1;
+                }
+
+                // Each constraint must still be satisfiable by its remaining unassigned spots
+                consistent &= self.needed[c] >= 0 && self.needed[c] <= self.unassigned[c];
+            }
+
+            let stopped = consistent && self.search(next + 1, mines_left - is_mine as i32, assignment, found);
+
+            for t in 0..self.touching[next].len() {
+                let c = self.touching[next][t];
+                self.unassigned[c] += 1;
+                if is_mine {
+                    self.needed[c] += 1;
+                }
+            }
+
+            if stopped {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Axis of symmetry of a minefield layout
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    /// Mirrored across the horizontal axis, so that the top half of the field mirrors the bottom half
+    Horizontal,
+
+    /// Mirrored across the vertical axis, so that the left half of the field mirrors the right half
+    Vertical,
+
+    /// Rotated by 180 degrees around the center of the field
+    Rotational,
+}
+
+impl Symmetry {
+    /// Mirror image of the given coordinates, in a field with the given dimensions
+    fn mirror(&self, x: Coord, y: Coord, width: Coord, height: Coord) -> (Coord, Coord) {
+        match self {
+            Symmetry::Horizontal => (x, height - 1 - y),
+            Symmetry::Vertical => (width - 1 - x, y),
+            Symmetry::Rotational => (width - 1 - x, height - 1 - y),
+        }
+    }
+}
+
+/// Coordinates of spots together with their new state, as produced by `Minefield::diff`
+pub type StateDiff = Vec<((Coord, Coord), SpotState)>;
+
+/// Errors when comparing or patching minefields
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DiffError {
+    /// The minefields don't have the same dimensions and holes
+    ShapeMismatch,
+
+    /// The given coordinates are outside the minefield
+    OutOfBounds { x: Coord, y: Coord },
+}
+
+impl std::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffError::ShapeMismatch => write!(f, "minefields have different shapes"),
+            DiffError::OutOfBounds { x, y } => write!(f, "coordinates ({}, {}) are outside the minefield", x, y),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+/// Errors when decoding a minefield from its binary format
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+    /// The data doesn't start with the magic bytes of the format
+    BadMagic,
+
+    /// The data was encoded with an unknown version of the format
+    UnsupportedVersion { version: u8 },
+
+    /// The data has the wrong length for its header, e.g. because it was truncated
+    Length { expected: usize, actual: usize },
+
+    /// The dimensions of the minefield are too large
+    TooLarge,
+
+    /// The byte of the spot at the given coordinates doesn't encode a spot
+    InvalidSpot { x: Coord, y: Coord },
+
+    /// The decoded minefield is inconsistent
+    Inconsistent { error: ValidationError },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "data is not an encoded minefield"),
+            DecodeError::UnsupportedVersion { version } => write!(f, "unsupported format version {}", version),
+            DecodeError::Length { expected, actual } => {
+                write!(f, "expected {} bytes of data instead of {}", expected, actual)
+            },
+            DecodeError::TooLarge => write!(f, "minefield dimensions are too large"),
+            DecodeError::InvalidSpot { x, y } => write!(f, "invalid spot at ({}, {})", x, y),
+            DecodeError::Inconsistent { error } => write!(f, "inconsistent minefield: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// An unbounded minefield for exploring, generated lazily in square chunks of `InfiniteMinefield::CHUNK_SIZE` spots.
+/// Whether a spot holds a mine only depends on the world seed, the density and its coordinates, so the contents of
+/// every chunk are the same whatever order the chunks are generated in. There is no game over: stepping on a mine
+/// explodes it, and the exploration may go on
+#[derive(Clone, Debug)]
+pub struct InfiniteMinefield {
+    /// Seed of the whole world
+    seed: u64,
+
+    /// Hashes of the coordinates below this threshold hold a mine
+    threshold: u64,
+
+    /// The generated chunks by chunk coordinates, each a row-major buffer of packed spots
+    chunks: HashMap<(i64, i64), Vec<PackedSpot>>,
+}
+
+impl InfiniteMinefield {
+    /// Width and height of a chunk
+    pub const CHUNK_SIZE: i64 = 64;
+
+    /// Maximum number of spots revealed by a single flood reveal. Sparse worlds may have unbounded empty regions, and
+    /// the flood stops at this limit, leaving revealed spots with hidden neighbors to step on
+    pub const FLOOD_LIMIT: usize = 16 * 64 * 64;
+
+    /// Create a world with the given seed, where the given share of the spots (between 0 and 1) hold a mine
+    pub fn new(seed: u64, density: f64) -> Self {
+        let threshold = (density.clamp(0.0, 1.0) * u64::MAX as f64) as u64;
+        InfiniteMinefield { seed, threshold, chunks: HashMap::new() }
+    }
+
+    /// The spot at the given coordinates. Spots of chunks which weren't generated yet are hidden
+    pub fn spot(&self, x: i64, y: i64) -> Spot {
+        match self.chunks.get(&Self::chunk_coords(x, y)) {
+            Some(chunk) => Spot::from(chunk[Self::chunk_index(x, y)]),
+            None => self.fresh_spot(x, y),
+        }
+    }
+
+    /// Step on the spot at the given coordinates, flood revealing across chunk borders (up to `FLOOD_LIMIT` spots) if
+    /// it has no neighboring mines
+    pub fn step(&mut self, x: i64, y: i64) -> StepResult {
+        let mut spot = self.spot(x, y);
+        let step_result = spot.step();
+        self.set_spot(x, y, spot);
+
+        if spot.state == (SpotState::RevealedEmpty { neighboring_mines: 0 }) {
+            let mut queued = HashSet::from([(x, y)]);
+            let mut spots_to_visit = VecDeque::from([(x, y)]);
+
+            while let Some((vx, vy)) = spots_to_visit.pop_front() {
+                for (nx, ny) in Self::neighbors_coords(vx, vy) {
+                    if queued.len() >= Self::FLOOD_LIMIT || !queued.insert((nx, ny)) {
+                        continue;
+                    }
+
+                    let mut n_spot = self.spot(nx, ny);
+
+                    if let SpotState::HiddenEmpty { neighboring_mines } |
+                        SpotState::QuestionedEmpty { neighboring_mines } = n_spot.state {
+                        n_spot.step();
+                        self.set_spot(nx, ny, n_spot);
+
+                        if neighboring_mines == 0 {
+                            spots_to_visit.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+
+        step_result
+    }
+
+    /// Automatically step on all hidden neighbors of a revealed spot, if it has as many flagged neighbors as
+    /// neighboring mines
+    pub fn auto_step(&mut self, x: i64, y: i64) -> StepResult {
+        let SpotState::RevealedEmpty { neighboring_mines } = self.spot(x, y).state else {
+            return StepResult::Invalid;
+        };
+
+        let placed_flags = Self::neighbors_coords(x, y).filter(|&(nx, ny)| self.spot(nx, ny).is_flagged()).count();
+
+        if placed_flags != neighboring_mines as usize {
+            return StepResult::Invalid;
+        }
+
+        for (nx, ny) in Self::neighbors_coords(x, y) {
+            if self.step(nx, ny) == StepResult::Boom {
+                return StepResult::Boom;
+            }
+        }
+
+        StepResult::Phew
+    }
+
+    /// Set a flag on a hidden spot, or clear the flag if the spot had one
+    pub fn toggle_flag(&mut self, x: i64, y: i64) -> FlagToggleResult {
+        let mut spot = self.spot(x, y);
+        let flag_result = spot.flag(FlagMode::Flags);
+
+        if flag_result != FlagToggleResult::None {
+            self.set_spot(x, y, spot);
+        }
+
+        flag_result
+    }
+
+    /// Iterator over the coordinates of the chunks generated so far, in no particular order. The chunk at `(cx, cy)`
+    /// holds the spots from `(cx * CHUNK_SIZE, cy * CHUNK_SIZE)` to `((cx + 1) * CHUNK_SIZE - 1, (cy + 1) * CHUNK_SIZE
+    /// - 1)`
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// Iterator over the spots of the chunk at the given chunk coordinates, together with their coordinates `(x, y)`,
+    /// in row-major order. Empty if the chunk wasn't generated yet
+    pub fn chunk_spots(&self, chunk_x: i64, chunk_y: i64) -> impl Iterator<Item = ((i64, i64), Spot)> + '_ {
+        self.chunks.get(&(chunk_x, chunk_y)).into_iter().flat_map(move |chunk| {
+            chunk.iter().enumerate().map(move |(index, packed)| {
+                let x = chunk_x * Self::CHUNK_SIZE + index as i64 % Self::CHUNK_SIZE;
+                let y = chunk_y * Self::CHUNK_SIZE + index as i64 / Self::CHUNK_SIZE;
+                ((x, y), Spot::from(*packed))
+            })
+        })
+    }
+
+    /// Store the given spot, generating its chunk if needed
+    fn set_spot(&mut self, x: i64, y: i64, spot: Spot) {
+        let (chunk_x, chunk_y) = Self::chunk_coords(x, y);
+
+        if !self.chunks.contains_key(&(chunk_x, chunk_y)) {
+            let chunk = (0..Self::CHUNK_SIZE * Self::CHUNK_SIZE)
+                .map(|index| {
+                    let x = chunk_x * Self::CHUNK_SIZE + index % Self::CHUNK_SIZE;
+                    let y = chunk_y * Self::CHUNK_SIZE + index / Self::CHUNK_SIZE;
+                    PackedSpot::from(self.fresh_spot(x, y))
+                })
+                .collect();
+            self.chunks.insert((chunk_x, chunk_y), chunk);
+        }
+
+        self.chunks.get_mut(&(chunk_x, chunk_y)).unwrap()[Self::chunk_index(x, y)] = PackedSpot::from(spot);
+    }
+
+    /// The hidden spot at the given coordinates, as generated
+    fn fresh_spot(&self, x: i64, y: i64) -> Spot {
+        let state = if self.is_mine(x, y) {
+            SpotState::HiddenMine
+        } else {
+            let neighboring_mines = Self::neighbors_coords(x, y).filter(|&(nx, ny)| self.is_mine(nx, ny)).count() as u8;
+            SpotState::HiddenEmpty { neighboring_mines }
+        };
+
+        Spot { state }
+    }
+
+    /// Whether the spot at the given coordinates holds a mine, from a hash of the seed and the coordinates
+    fn is_mine(&self, x: i64, y: i64) -> bool {
+        let bytes = self.seed.to_le_bytes().into_iter().chain(x.to_le_bytes()).chain(y.to_le_bytes());
+        SplitMix64(fnv1a(bytes)).next_u64() < self.threshold
+    }
+
+    /// Coordinates of the chunk holding the spot at the given coordinates
+    fn chunk_coords(x: i64, y: i64) -> (i64, i64) {
+        (x.div_euclid(Self::CHUNK_SIZE), y.div_euclid(Self::CHUNK_SIZE))
+    }
+
+    /// Index of the spot at the given coordinates in the buffer of its chunk
+    fn chunk_index(x: i64, y: i64) -> usize {
+        (y.rem_euclid(Self::CHUNK_SIZE) * Self::CHUNK_SIZE + x.rem_euclid(Self::CHUNK_SIZE)) as usize
+    }
+
+    /// Iterator over the coordinates of the 8 neighbors of the given coordinates
+    fn neighbors_coords(x: i64, y: i64) -> impl Iterator<Item = (i64, i64)> {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&coords| coords != (x, y))
+    }
+}
+
+/// 64 bit FNV-1a hash, which is simple and stable across platforms and releases
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes
+        .into_iter()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// SplitMix64 pseudo-random number generator. Unlike the generators of `rand`, its output is frozen, which daily
+/// challenge minefields rely on
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Regenerate the minefield of a recorded game from its dimensions, mines and seed (see
+/// `Minefield::with_seeded_mines`), replay the recorded actions on it, and report the outcome
+pub fn verify_replay(
+    width: Coord,
+    height: Coord,
+    mines: u32,
+    seed: u64,
+    actions: impl IntoIterator<Item = Action>,
+) -> ReplayVerdict {
+    let mut minefield = Minefield::new(width, height).with_seeded_mines(mines, seed);
+    let mut invalid_actions = 0;
+    let mut exploded = false;
+
+    for (action_index, action) in actions.into_iter().enumerate() {
+        if exploded {
+            return ReplayVerdict::ActionAfterBoom { action_index };
+        }
+
+        let (x, y) = match action {
+            Action::Step { x, y } |
+            Action::AutoStep { x, y } |
+            Action::ToggleFlag { x, y } |
+            Action::SmartChord { x, y } => (x, y),
+        };
+
+        if minefield.spot(x, y).is_none() {
+            return ReplayVerdict::OutOfBounds { action_index };
+        }
+
+        match minefield.apply(action).outcome {
+            ActionOutcome::Step(StepResult::Boom) => exploded = true,
+            ActionOutcome::Step(StepResult::Invalid) |
+            ActionOutcome::Flag(FlagToggleResult::None | FlagToggleResult::LimitReached) => {
+                invalid_actions += 1;
+            },
+            _ => {},
+        }
+    }
+
+    ReplayVerdict::Completed {
+        cleared: minefield.is_cleared(),
+        invalid_actions,
+        state_hash: minefield.state_hash(),
+    }
+}
+
+/// The outcome of verifying a replay with `verify_replay`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ReplayVerdict {
+    /// All actions of the replay were applied
+    Completed {
+        /// Whether the minefield was cleared at the end of the replay
+        cleared: bool,
+
+        /// Number of actions which had no effect on the minefield
+        invalid_actions: u32,
+
+        /// Hash of the final state of the minefield (see `Minefield::state_hash`)
+        state_hash: u64,
+    },
+
+    /// The action at the given index of the replay targets coordinates outside the minefield
+    OutOfBounds { action_index: usize },
+
+    /// The action at the given index of the replay comes after a mine was stepped on
+    ActionAfterBoom { action_index: usize },
+}
+
+/// The layout of a minefield at the start of a game, from which `ReplayPlayer` rebuilds it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BoardSpec {
+    /// Width of the field grid
+    pub width: Coord,
+
+    /// Height of the field grid
+    pub height: Coord,
+
+    /// Coordinates of the field grid without a spot
+    pub holes: Vec<(Coord, Coord)>,
+
+    /// Coordinates of the mines, in row-major order
+    pub mines: Vec<(Coord, Coord)>,
+
+    /// Whether a mine under the very first step is moved away (see `Minefield::with_move_first_mine`)
+    pub move_first_mine: bool,
+
+    /// Coordinates of the spots which never receive a randomly placed mine, in row-major order (see
+    /// `Minefield::with_reserved_safe`)
+    pub reserved: Vec<(Coord, Coord)>,
+
+    /// Whether stepping on a spot with no neighboring mines floods (see `Minefield::with_flood`)
+    pub flood_enabled: bool,
+
+    /// Whether flood reveals continue through flags (see `Minefield::with_flood_through_flags`)
+    pub flood_through_flags: bool,
+
+    /// Which marks toggling a flag cycles through (see `Minefield::set_flag_mode`)
+    pub flag_mode: FlagMode,
+
+    /// Whether no more flags than mines can be placed (see `Minefield::limit_flags_to_mines`)
+    pub limit_flags: bool,
+
+    /// Whether flagging every mine steps on the remaining spots (see `Minefield::auto_finish_on_full_flag`)
+    pub auto_finish: bool,
+
+    /// Whether the edges of the field grid wrap around (see `Minefield::with_wrapping`)
+    pub wrapping: bool,
+
+    /// Seed of the generator rearranging the mines, if they are placed adversarially (see
+    /// `Minefield::with_seeded_adversarial`)
+    #[cfg(feature = "adversarial")]
+    pub adversarial_seed: Option<u64>,
+}
+
+impl BoardSpec {
+    /// The layout of the given minefield, with the options which change how the actions play out. Spot states are
+    /// not part of it
+    pub fn of<T>(minefield: &Minefield<T>) -> Self {
+        let holes = (0..minefield.field.len())
+            .filter(|&index| minefield.field[index] == PackedSpot::HOLE)
+            .map(|index| minefield.coords(index))
+            .collect();
+        let mines = minefield.spots().filter(|(_coords, spot)| spot.is_mine()).map(|(coords, _spot)| coords).collect();
+        let mut reserved: Vec<usize> = minefield.reserved.iter().copied().collect();
+        reserved.sort_unstable();
+
+        BoardSpec {
+            width: minefield.width,
+            height: minefield.height,
+            holes,
+            mines,
+            move_first_mine: minefield.move_first_mine,
+            reserved: reserved.into_iter().map(|index| minefield.coords(index)).collect(),
+            flood_enabled: minefield.flood_enabled,
+            flood_through_flags: minefield.flood_through_flags,
+            flag_mode: minefield.flag_mode,
+            limit_flags: minefield.limit_flags,
+            auto_finish: minefield.auto_finish,
+            wrapping: minefield.wrapping,
+            #[cfg(feature = "adversarial")]
+            adversarial_seed: minefield.adversary.as_ref().map(|adversary| adversary.seed),
+        }
+    }
+
+    /// Build a minefield with this layout and these options, with every spot hidden
+    pub fn build(&self) -> Minefield {
+        let holes: HashSet<(Coord, Coord)> = self.holes.iter().copied().collect();
+        let mut minefield = Minefield::with_shape(self.width, self.height, |x, y| !holes.contains(&(x, y)))
+            .with_move_first_mine(self.move_first_mine)
+            .with_reserved_safe(self.reserved.iter().copied())
+            .with_flood(self.flood_enabled)
+            .with_flood_through_flags(self.flood_through_flags)
+            .limit_flags_to_mines(self.limit_flags)
+            .auto_finish_on_full_flag(self.auto_finish)
+            .with_wrapping(self.wrapping);
+        minefield.set_flag_mode(self.flag_mode);
+
+        for &(x, y) in &self.mines {
+            minefield.place_mine(x, y).ok();
+        }
+
+        #[cfg(feature = "adversarial")]
+        if let Some(seed) = self.adversarial_seed {
+            minefield = minefield.with_seeded_adversarial(seed);
+        }
+
+        minefield
+    }
+}
+
+/// A game being recorded: every action applied through the replay is applied to the minefield and logged, so that
+/// `ReplayPlayer` can play the game back
+#[derive(Debug)]
+pub struct Replay<'a, T = ()> {
+    /// The recorded minefield
+    minefield: &'a mut Minefield<T>,
+
+    /// Layout of the minefield when recording started
+    spec: BoardSpec,
+
+    /// Actions applied since recording started, the action at index `n` being move `n`
+    actions: Vec<Action>,
+}
+
+impl<'a, T> Replay<'a, T> {
+    /// Start recording a game on the given minefield, which should not have been played yet. Minefields rearranged
+    /// during play by `with_adversarial` play back the same from the start of their game
+    pub fn record(minefield: &'a mut Minefield<T>) -> Self {
+        Replay { spec: BoardSpec::of(minefield), minefield, actions: Vec::new() }
+    }
+
+    /// Apply a player action to the recorded minefield (see `Minefield::apply`), and log it
+    pub fn apply(&mut self, action: Action) -> ActionResult {
+        self.actions.push(action);
+        self.minefield.apply(action)
+    }
+
+    /// The recorded minefield
+    pub fn minefield(&self) -> &Minefield<T> {
+        self.minefield
+    }
+
+    /// The actions logged so far, in the order they were applied
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Stop recording, and hand back the layout of the minefield when recording started and the logged actions
+    pub fn into_parts(self) -> (BoardSpec, Vec<Action>) {
+        (self.spec, self.actions)
+    }
+}
+
+/// Plays a recorded game back, move by move, e.g. to scrub through it in a review of the game
+#[derive(Clone, Debug)]
+pub struct ReplayPlayer {
+    /// Layout of the minefield at the start of the game
+    spec: BoardSpec,
+
+    /// The recorded actions
+    actions: Vec<Action>,
+
+    /// The minefield after the first `position` actions
+    minefield: Minefield,
+
+    /// Number of actions applied to the minefield
+    position: usize,
+}
+
+impl ReplayPlayer {
+    /// Start playing back the given actions on a minefield built from the given layout, before the first action
+    pub fn new(spec: BoardSpec, actions: Vec<Action>) -> Self {
+        let minefield = spec.build();
+        ReplayPlayer { spec, actions, minefield, position: 0 }
+    }
+
+    /// The minefield at the current position of the playback
+    pub fn minefield(&self) -> &Minefield {
+        &self.minefield
+    }
+
+    /// Number of actions played back so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of recorded actions
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Whether no action was recorded
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Play back the next action, and return its result, or `None` at the end of the recording
+    pub fn step_forward(&mut self) -> Option<ActionResult> {
+        let action = *self.actions.get(self.position)?;
+        self.position += 1;
+        Some(self.minefield.apply(action))
+    }
+
+    /// Undo the last action played back. Returns `false` at the start of the recording
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+
+        self.seek(self.position - 1);
+        true
+    }
+
+    /// Move the playback to just after the first `position` actions (or to the end of the recording), and return the
+    /// minefield there. Seeking backwards plays the game again from the start
+    pub fn seek(&mut self, position: usize) -> &Minefield {
+        let position = position.min(self.actions.len());
+
+        if position < self.position {
+            self.minefield = self.spec.build();
+            self.position = 0;
+        }
+
+        while self.position < position {
+            self.step_forward();
+        }
+
+        &self.minefield
+    }
+}
+
+/// An action recorded in the history of a minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TimestampedAction {
+    /// The applied action
+    pub action: Action,
+
+    /// Time elapsed between enabling history recording and applying the action
+    pub elapsed: Duration,
+}
+
+/// Standard settings of a game, to group statistics by
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Difficulty {
+    /// 9x9 spots with 10 mines
+    Beginner,
+
+    /// 16x16 spots with 40 mines
+    Intermediate,
+
+    /// 30x16 spots with 99 mines
+    Expert,
+
+    /// Any other width, height and number of mines
+    Custom { width: Coord, height: Coord, mines: u32 },
+}
+
+impl Difficulty {
+    /// The width, height and number of mines of a minefield of this difficulty
+    pub fn settings(&self) -> (Coord, Coord, u32) {
+        match *self {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (30, 16, 99),
+            Difficulty::Custom { width, height, mines } => (width, height, mines),
+        }
+    }
+}
+
+/// The outcome of a finished game, as recorded in `Stats`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+    /// Whether the game was won
+    pub won: bool,
+
+    /// Number of actions which changed the minefield
+    pub moves: u32,
+
+    /// 3BV of the minefield (see `Minefield::bbbv`)
+    pub bbbv: usize,
+
+    /// Time the game took
+    pub elapsed: Duration,
+}
+
+impl GameRecord {
+    /// The record of a game finished on the given minefield in the given time, or `None` if the game is still in
+    /// progress
+    pub fn from_minefield<T>(minefield: &Minefield<T>, elapsed: Duration) -> Option<Self> {
+        let won = match minefield.game_state() {
+            GameState::Won => true,
+            GameState::Lost => false,
+            GameState::InProgress => return None,
+        };
+
+        Some(GameRecord { won, moves: minefield.move_count(), bbbv: minefield.bbbv(), elapsed })
+    }
+
+    /// 3BV cleared per second, or `None` if no time elapsed
+    pub fn bbbv_per_second(&self) -> Option<f64> {
+        let seconds = self.elapsed.as_secs_f64();
+        (seconds > 0.0).then(|| self.bbbv as f64 / seconds)
+    }
+}
+
+/// Running totals and best records of the games played at one difficulty
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct DifficultyStats {
+    /// Number of games recorded
+    pub games: u32,
+
+    /// Number of games won
+    pub wins: u32,
+
+    /// Moves made in all games
+    pub total_moves: u64,
+
+    /// 3BV of all minefields
+    pub total_bbbv: u64,
+
+    /// Time taken by all games
+    pub total_time: Duration,
+
+    /// Fastest won game
+    pub best_time: Option<Duration>,
+
+    /// Most 3BV cleared per second in a won game
+    pub best_bbbv_per_second: Option<f64>,
+}
+
+impl DifficultyStats {
+    /// Share of the games which were won, or 0 if no game was recorded
+    pub fn win_rate(&self) -> f64 {
+        if self.games > 0 { self.wins as f64 / self.games as f64 } else { 0.0 }
+    }
+
+    /// Add a finished game to the totals, and to the best records if it was won
+    pub fn record(&mut self, game: GameRecord) {
+        self.games += 1;
+        self.total_moves += game.moves as u64;
+        self.total_bbbv += game.bbbv as u64;
+        self.total_time += game.elapsed;
+
+        if game.won {
+            self.wins += 1;
+            self.best_time = Some(self.best_time.map_or(game.elapsed, |best| best.min(game.elapsed)));
+
+            if let Some(bbbv_per_second) = game.bbbv_per_second() {
+                self.best_bbbv_per_second =
+                    Some(self.best_bbbv_per_second.map_or(bbbv_per_second, |best| best.max(bbbv_per_second)));
+            }
+        }
+    }
+}
+
+/// Statistics of finished games, per difficulty
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Stats {
+    /// Statistics of each difficulty with at least one recorded game
+    by_difficulty: HashMap<Difficulty, DifficultyStats>,
+}
+
+impl Stats {
+    /// Record a finished game played at the given difficulty
+    pub fn record(&mut self, difficulty: Difficulty, game: GameRecord) {
+        self.by_difficulty.entry(difficulty).or_default().record(game);
+    }
+
+    /// Statistics of the games played at the given difficulty, or `None` if none was recorded
+    pub fn difficulty(&self, difficulty: Difficulty) -> Option<&DifficultyStats> {
+        self.by_difficulty.get(&difficulty)
+    }
+
+    /// Iterator over every difficulty with recorded games, together with its statistics
+    pub fn difficulties(&self) -> impl Iterator<Item = (Difficulty, &DifficultyStats)> + '_ {
+        self.by_difficulty.iter().map(|(difficulty, stats)| (*difficulty, stats))
+    }
+}
+
+/// Builder of a `Minefield`, which collects all options and checks that they agree with each other when building
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MinefieldBuilder {
+    width: Option<Coord>,
+    height: Option<Coord>,
+    mines: Option<u32>,
+    density: Option<f64>,
+    seed: Option<u64>,
+    reserved_safe: Vec<(Coord, Coord)>,
+    question_marks: bool,
+    wrapping: bool,
+}
+
+impl MinefieldBuilder {
+    /// Width of the minefield, which is required
+    pub fn width(mut self, width: Coord) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Height of the minefield, which is required
+    pub fn height(mut self, height: Coord) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Number of mines randomly placed in the minefield. Can't be combined with `density`
+    pub fn mines(mut self, mines: u32) -> Self {
+        self.mines = Some(mines);
+        self
+    }
+
+    /// Share of the spots, between 0 and 1, which randomly receive a mine. Can't be combined with `mines`
+    pub fn density(mut self, density: f64) -> Self {
+        self.density = Some(density);
+        self
+    }
+
+    /// Seed of the random placement of mines, so that the same options always build the same minefield
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Spots which never receive a mine, like `Minefield::with_reserved_safe`
+    pub fn reserved_safe(mut self, cells: impl IntoIterator<Item = (Coord, Coord)>) -> Self {
+        self.reserved_safe.extend(cells);
+        self
+    }
+
+    /// Whether toggling a flag also cycles through question marks, like `FlagMode::FlagsAndQuestionMarks`
+    pub fn question_marks(mut self, question_marks: bool) -> Self {
+        self.question_marks = question_marks;
+        self
+    }
+
+    /// Whether the edges of the minefield wrap around, like `Minefield::with_wrapping`
+    pub fn wrapping(mut self, wrapping: bool) -> Self {
+        self.wrapping = wrapping;
+        self
+    }
+
+    /// Build the minefield, or report the first inconsistency among the options
+    pub fn build(self) -> Result<Minefield, BuildError> {
+        let (Some(width), Some(height)) = (self.width, self.height) else {
+            return Err(BuildError::MissingSize);
+        };
+
+        let minefield = Minefield::try_new(width, height).map_err(|error| match error {
+            SizeError::Empty => BuildError::EmptySize,
+            SizeError::TooLarge { width, height } => BuildError::TooLarge { width, height },
+        })?;
+
+        if let Some((x, y)) = self.reserved_safe.iter().copied().find(|&(x, y)| x >= width || y >= height) {
+            return Err(BuildError::ReservedOutOfBounds { x, y });
+        }
+
+        let mut minefield = minefield.with_reserved_safe(self.reserved_safe).with_wrapping(self.wrapping);
+        let free = (minefield.cell_count() - minefield.reserved.len()) as u32;
+
+        let mines = match (self.mines, self.density) {
+            (Some(_), Some(_)) => return Err(BuildError::MinesAndDensity),
+            (Some(mines), None) => mines,
+            (None, Some(density)) if (0.0..=1.0).contains(&density) => {
+                (density * minefield.cell_count() as f64).round() as u32
+            },
+            (None, Some(density)) => return Err(BuildError::InvalidDensity { density }),
+            (None, None) => 0,
+        };
+
+        if mines > free {
+            return Err(BuildError::TooManyMines { mines, max: free });
+        }
+
+        minefield = match self.seed {
+            Some(seed) => minefield.with_seeded_mines(mines, seed),
+            None => minefield.with_mines(mines),
+        };
+
+        if self.question_marks {
+            minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        }
+
+        Ok(minefield)
+    }
+}
+
+/// A minefield found by `Minefield::generate_with_bbbv`
+#[derive(Clone, Debug)]
+pub struct Generated {
+    /// The generated minefield
+    pub minefield: Minefield,
+
+    /// Number of minefields generated until this one was found, including this one
+    pub attempts: u32,
+
+    /// Seed of this minefield, for `Minefield::with_seeded_mines`
+    pub seed: u64,
+}
+
+/// Breakdown of the neighbors of a spot, as seen by the player
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct NeighborSummary {
+    /// Neighbors which are hidden, without a flag
+    pub hidden: u8,
+
+    /// Neighbors which carry a flag
+    pub flagged: u8,
+
+    /// Neighbors which are visible to the player
+    pub revealed: u8,
+
+    /// Neighbors which are visible to the player as mines
+    pub mines_shown: u8,
+}
+
+/// What the player can prove about the unknown spots of a minefield, as returned by `Minefield::deduce()`
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Deductions {
+    /// Coordinates `(x, y)` of the spots which certainly don't hold a mine, in row-major order
+    pub safe: Vec<(Coord, Coord)>,
+
+    /// Coordinates `(x, y)` of the spots which certainly hold a mine, in row-major order
+    pub mines: Vec<(Coord, Coord)>,
+}
+
+/// Read-only view of the true contents of a minefield, as returned by `Minefield::oracle()`. Enabling the `oracle`
+/// feature makes it available, and with it the position of every mine
+#[cfg(feature = "oracle")]
+#[derive(Copy, Clone, Debug)]
+pub struct Oracle<'a, T = ()> {
+    minefield: &'a Minefield<T>,
+}
+
+#[cfg(feature = "oracle")]
+impl<T> Oracle<'_, T> {
+    /// Does the spot at the given coordinates hold a mine, whether it is hidden, flagged or revealed? `false` outside
+    /// the minefield and for holes
+    pub fn is_mine(&self, x: Coord, y: Coord) -> bool {
+        self.minefield.spot(x, y).is_some_and(|spot| spot.is_mine())
+    }
+
+    /// Which coordinates of the field grid hold a mine, in row-major order. Holes hold no mine
+    pub fn mine_mask(&self) -> Vec<bool> {
+        self.minefield.mine_layout()
+    }
+
+    /// Number of mines around every coordinate of the field grid, in row-major order, including around mines. Holes
+    /// count as 0
+    pub fn true_counts(&self) -> Vec<u8> {
+        let minefield = self.minefield;
+
+        (0..minefield.field.len())
+            .map(|index| {
+                if minefield.field[index] == PackedSpot::HOLE {
+                    0
+                } else {
+                    minefield.neighbor_indices(index).filter(|&n_index| minefield.get(n_index).is_mine()).count() as u8
+                }
+            })
+            .collect()
+    }
+}
+
+/// Number of spots in each `SpotState`, as returned by `Minefield::state_histogram()`
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct StateHistogram {
+    /// Spots in `SpotState::HiddenEmpty`
+    pub hidden_empty: usize,
+
+    /// Spots in `SpotState::HiddenMine`
+    pub hidden_mine: usize,
+
+    /// Spots in `SpotState::FlaggedEmpty`
+    pub flagged_empty: usize,
+
+    /// Spots in `SpotState::FlaggedMine`
+    pub flagged_mine: usize,
+
+    /// Spots in `SpotState::RevealedEmpty`
+    pub revealed_empty: usize,
+
+    /// Spots in `SpotState::ExplodedMine`
+    pub exploded_mine: usize,
+
+    /// Spots in `SpotState::WrongFlag`
+    pub wrong_flag: usize,
+
+    /// Spots in `SpotState::RevealedMine`
+    pub revealed_mine: usize,
+
+    /// Spots in `SpotState::QuestionedEmpty`
+    pub questioned_empty: usize,
+
+    /// Spots in `SpotState::QuestionedMine`
+    pub questioned_mine: usize,
+}
+
+impl StateHistogram {
+    /// Number of spots counted in all states
+    pub fn total(&self) -> usize {
+        self.hidden_empty +
+            self.hidden_mine +
+            self.flagged_empty +
+            self.flagged_mine +
+            self.revealed_empty +
+            self.exploded_mine +
+            self.wrong_flag +
+            self.revealed_mine +
+            self.questioned_empty +
+            self.questioned_mine
+    }
+}
+
+/// Statistics about the mine layout of a minefield
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LayoutStats {
+    /// Number of openings, i.e. regions of connected empty spots with no neighboring mines
+    pub openings: usize,
+
+    /// Number of spots revealed by a single step into the largest opening
+    pub largest_opening: usize,
+
+    /// Number of regions of connected empty spots, enclosed by mines and the edges of the field, which contain no
+    /// opening. These can't be cleared without guessing
+    pub isolated_regions: usize,
+
+    /// Share of the spots which hold a mine
+    pub density: f64,
+}
+
+/// Whether the flags placed on a minefield can all be right, as reported by `Minefield::is_consistent`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConsistencyReport {
+    /// Some placement of the remaining mines satisfies every revealed count
+    Consistent,
+
+    /// No revealed count is violated on its own, but there were too many hidden spots next to revealed spots to check
+    /// all counts together
+    LocallyConsistent,
+
+    /// The revealed count at the given coordinates has too many flags around it, or too few hidden neighbors left
+    Violated { x: Coord, y: Coord },
+
+    /// More flags are placed than there are mines
+    TooManyFlags { flags: u32, mines: u32 },
+
+    /// Every revealed count can be satisfied on its own, but not all of them together
+    Contradiction,
+}
+
+/// The moves made by `Minefield::solve_step`
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SolveStepReport {
+    /// Coordinates of the spots which were flagged
+    pub flagged: Vec<(Coord, Coord)>,
+
+    /// Coordinates of the spots which were revealed (or exploded)
+    pub revealed: Vec<(Coord, Coord)>,
+}
+
+impl SolveStepReport {
+    /// Whether no move was made
+    pub fn is_empty(&self) -> bool {
+        self.flagged.is_empty() && self.revealed.is_empty()
+    }
+}
+
+/// The result of stepping on several spots with `Minefield::step_many`, or of `Minefield::auto_step_cascade`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BatchStepResult {
+    /// Result of each step (or automatic step), in order, up to and including the first step on a mine
+    pub results: Vec<StepResult>,
+
+    /// Coordinates of every spot revealed or exploded by the batch
+    pub revealed: Vec<(Coord, Coord)>,
+
+    /// Index in the batch of the step on a mine which stopped the batch, and coordinates of the exploded mine
+    pub boom: Option<(usize, (Coord, Coord))>,
+}
+
+/// Recorded history of a minefield
+#[derive(Clone, Debug)]
+struct History {
+    /// When history recording was enabled
+    start: Instant,
+
+    /// Actions applied since recording was enabled
+    actions: Vec<TimestampedAction>,
+}
+
+/// Errors when editing the mines of a minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EditError {
+    /// The given coordinates are outside the minefield
+    OutOfBounds { x: Coord, y: Coord },
+
+    /// The spot at the given coordinates already holds a mine
+    AlreadyMine { x: Coord, y: Coord },
+
+    /// The spot at the given coordinates doesn't hold a mine
+    NotAMine { x: Coord, y: Coord },
+
+    /// The spot at the given coordinates was already revealed, or exploded
+    Revealed { x: Coord, y: Coord },
+}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditError::OutOfBounds { x, y } => write!(f, "coordinates ({}, {}) are outside the minefield", x, y),
+            EditError::AlreadyMine { x, y } => write!(f, "spot ({}, {}) already holds a mine", x, y),
+            EditError::NotAMine { x, y } => write!(f, "spot ({}, {}) doesn't hold a mine", x, y),
+            EditError::Revealed { x, y } => write!(f, "spot ({}, {}) was already revealed", x, y),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// Inconsistent options found when building a minefield with a `MinefieldBuilder`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BuildError {
+    /// The width or the height was not set
+    MissingSize,
+
+    /// The width or the height is zero
+    EmptySize,
+
+    /// The field grid would have more than `Minefield::MAX_CELLS` coordinates
+    TooLarge { width: Coord, height: Coord },
+
+    /// Both the number of mines and the density of mines were set
+    MinesAndDensity,
+
+    /// The density of mines is not between 0 and 1
+    InvalidDensity { density: f64 },
+
+    /// More mines were requested than there are spots which may receive one
+    TooManyMines { mines: u32, max: u32 },
+
+    /// A reserved spot is outside the minefield
+    ReservedOutOfBounds { x: Coord, y: Coord },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::MissingSize => write!(f, "the width and the height of the minefield are required"),
+            BuildError::EmptySize => write!(f, "the width and the height of the minefield can't be zero"),
+            BuildError::TooLarge { width, height } => write!(f, "a {}x{} minefield is too large", width, height),
+            BuildError::MinesAndDensity => write!(f, "the number of mines and the density of mines are exclusive"),
+            BuildError::InvalidDensity { density } => write!(f, "density {} is not between 0 and 1", density),
+            BuildError::TooManyMines { mines, max } => {
+                write!(f, "{} mines requested, but only {} spots may receive one", mines, max)
+            },
+            BuildError::ReservedOutOfBounds { x, y } => {
+                write!(f, "reserved coordinates ({}, {}) are outside the minefield", x, y)
+            },
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Errors when creating a minefield of a given size
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SizeError {
+    /// The width or the height is zero
+    Empty,
+
+    /// The field grid would have more than `Minefield::MAX_CELLS` coordinates
+    TooLarge { width: Coord, height: Coord },
+}
+
+impl std::fmt::Display for SizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizeError::Empty => write!(f, "the width and the height of the minefield can't be zero"),
+            SizeError::TooLarge { width, height } => write!(f, "a {}x{} minefield is too large", width, height),
+        }
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+/// Errors when generating a minefield with given properties
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GenerationError {
+    /// None of the generated minefields had the requested properties
+    NoMatch { attempts: u32 },
+}
+
+impl std::fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationError::NoMatch { attempts } => write!(f, "no match found in {} attempts", attempts),
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
+/// Errors when placing mines under a constraint
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    /// More mines were requested than the constraint leaves room for
+    TooManyMines { mines: u32, max: u32 },
+
+    /// The given coordinates are outside the minefield
+    OutOfBounds { x: Coord, y: Coord },
+
+    /// The requested mines don't fit the spots left free by holes, reserved spots and mines already in place
+    Unsatisfiable { mines: u32 },
+}
+
+impl std::fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlacementError::TooManyMines { mines, max } => {
+                write!(f, "{} mines requested, but at most {} fit the constraint", mines, max)
+            },
+            PlacementError::OutOfBounds { x, y } => write!(f, "coordinates ({}, {}) are outside the minefield", x, y),
+            PlacementError::Unsatisfiable { mines } => write!(f, "no room left to place {} mines", mines),
+        }
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+/// An inconsistency found when validating a minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ValidationError {
+    /// The field doesn't hold exactly one spot for each coordinate of its grid
+    SpotCount { stored: usize, actual: usize },
+
+    /// An empty spot holds the wrong count of neighboring mines
+    NeighborCount { x: Coord, y: Coord, stored: u8, actual: u8 },
+
+    /// The number of mines of the minefield doesn't match the mines in its field
+    MineCount { stored: u32, actual: u32 },
+
+    /// The number of flags of the minefield doesn't match the flags in its field
+    FlagCount { stored: u32, actual: u32 },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::SpotCount { stored, actual } => {
+                write!(f, "field holds {} spots instead of {}", stored, actual)
+            },
+            ValidationError::NeighborCount { x, y, stored, actual } => {
+                write!(f, "spot ({}, {}) counts {} neighboring mines instead of {}", x, y, stored, actual)
+            },
+            ValidationError::MineCount { stored, actual } => {
+                write!(f, "minefield counts {} mines instead of {}", stored, actual)
+            },
+            ValidationError::FlagCount { stored, actual } => {
+                write!(f, "minefield counts {} flags instead of {}", stored, actual)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A `Spot` packed into a single byte, as stored in the minefield buffer.
+///
+/// Bits `0..4` hold the neighboring mines count (0 to 8), and bits `4..8` hold the `SpotState` variant. Holes in shaped
+/// fields use a dedicated state value, and never decode into a `Spot`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+struct PackedSpot(u8);
+
+impl PackedSpot {
+    const COUNT_MASK: u8 = 0b0000_1111;
+    const STATE_SHIFT: u8 = 4;
+
+    const HIDDEN_EMPTY: u8 = 0;
+    const HIDDEN_MINE: u8 = 1;
+    const FLAGGED_EMPTY: u8 = 2;
+    const FLAGGED_MINE: u8 = 3;
+    const REVEALED_EMPTY: u8 = 4;
+    const EXPLODED_MINE: u8 = 5;
+    const WRONG_FLAG: u8 = 6;
+    const REVEALED_MINE: u8 = 7;
+    const HOLE_STATE: u8 = 8;
+    const QUESTIONED_EMPTY: u8 = 9;
+    const QUESTIONED_MINE: u8 = 10;
+
+    /// Coordinates in the field grid which hold no spot
+    const HOLE: PackedSpot = PackedSpot(Self::HOLE_STATE << Self::STATE_SHIFT);
+
+    fn new(state: u8, neighboring_mines: u8) -> Self {
+        debug_assert!(neighboring_mines <= 8);
+        Self(state << Self::STATE_SHIFT | neighboring_mines & Self::COUNT_MASK)
+    }
+
+    /// Does this byte decode into a `Spot`, and encode back into the same byte?
+    fn is_valid(self) -> bool {
+        let state = self.0 >> Self::STATE_SHIFT;
+        let neighboring_mines = self.0 & Self::COUNT_MASK;
+
+        // Check the count before encoding it again, which only accepts valid counts
+        let max_count = match state {
+            Self::HIDDEN_EMPTY |
+            Self::FLAGGED_EMPTY |
+            Self::REVEALED_EMPTY |
+            Self::WRONG_FLAG |
+            Self::QUESTIONED_EMPTY => 8,
+            Self::HIDDEN_MINE |
+            Self::FLAGGED_MINE |
+            Self::EXPLODED_MINE |
+            Self::REVEALED_MINE |
+            Self::QUESTIONED_MINE => 0,
+            _ => return false,
+        };
+
+        neighboring_mines <= max_count && PackedSpot::from(Spot::from(self)) == self
+    }
+}
+
+impl From<Spot> for PackedSpot {
+    fn from(spot: Spot) -> Self {
+        match spot.state {
+            SpotState::HiddenEmpty { neighboring_mines } => Self::new(Self::HIDDEN_EMPTY, neighboring_mines),
+            SpotState::HiddenMine => Self::new(Self::HIDDEN_MINE, 0),
+            SpotState::FlaggedEmpty { neighboring_mines } => Self::new(Self::FLAGGED_EMPTY, neighboring_mines),
+            SpotState::FlaggedMine => Self::new(Self::FLAGGED_MINE, 0),
+            SpotState::RevealedEmpty { neighboring_mines } => Self::new(Self::REVEALED_EMPTY, neighboring_mines),
+            SpotState::ExplodedMine => Self::new(Self::EXPLODED_MINE, 0),
+            SpotState::WrongFlag { neighboring_mines } => Self::new(Self::WRONG_FLAG, neighboring_mines),
+            SpotState::RevealedMine => Self::new(Self::REVEALED_MINE, 0),
+            SpotState::QuestionedEmpty { neighboring_mines } => Self::new(Self::QUESTIONED_EMPTY, neighboring_mines),
+            SpotState::QuestionedMine => Self::new(Self::QUESTIONED_MINE, 0),
+        }
+    }
+}
+
+impl From<PackedSpot> for Spot {
+    fn from(packed: PackedSpot) -> Self {
+        let neighboring_mines = packed.0 & PackedSpot::COUNT_MASK;
+
+        let state = match packed.0 >> PackedSpot::STATE_SHIFT {
+            PackedSpot::HIDDEN_EMPTY => SpotState::HiddenEmpty { neighboring_mines },
+            PackedSpot::HIDDEN_MINE => SpotState::HiddenMine,
+            PackedSpot::FLAGGED_EMPTY => SpotState::FlaggedEmpty { neighboring_mines },
+            PackedSpot::FLAGGED_MINE => SpotState::FlaggedMine,
+            PackedSpot::REVEALED_EMPTY => SpotState::RevealedEmpty { neighboring_mines },
+            PackedSpot::EXPLODED_MINE => SpotState::ExplodedMine,
+            PackedSpot::WRONG_FLAG => SpotState::WrongFlag { neighboring_mines },
+            PackedSpot::REVEALED_MINE => SpotState::RevealedMine,
+            PackedSpot::QUESTIONED_EMPTY => SpotState::QuestionedEmpty { neighboring_mines },
+            PackedSpot::QUESTIONED_MINE => SpotState::QuestionedMine,
+            _ => unreachable!("invalid packed spot {:#010b}", packed.0),
+        };
+
+        Self { state }
+    }
+}
+
+/// The overall state of a game on the minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GameState {
+    /// Some empty spots are still hidden, and no mine has exploded
+    InProgress,
+
+    /// Every empty spot has been revealed, without any mine exploding
+    Won,
+
+    /// A mine has exploded
+    Lost,
+}
+
+/// A spot revealed by `Minefield::hint`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Hint {
+    /// The spot was provably safe
+    Safe { x: Coord, y: Coord },
+
+    /// No spot was provably safe, so the spot least likely to hold a mine was stepped on, with the given result
+    Guess { x: Coord, y: Coord, result: StepResult },
+}
+
+/// A spot pointed out by `Minefield::hint_explained`, with the reason
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExplainedHint {
+    /// Coordinates `(x, y)` of the hidden spot
+    pub coords: (Coord, Coord),
+
+    /// What is proven about the spot
+    pub kind: HintKind,
+
+    /// The revealed counts which together prove it, in row-major order
+    pub because: Vec<ConstraintRef>,
+}
+
+/// What an `ExplainedHint` proves about its spot
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HintKind {
+    /// The spot is safe to step on
+    Safe,
+
+    /// The spot holds a mine
+    Mine,
+}
+
+/// A revealed spot whose count is part of the proof of an `ExplainedHint`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ConstraintRef {
+    /// Coordinates `(x, y)` of the revealed spot
+    pub coords: (Coord, Coord),
+
+    /// Its count of neighboring mines
+    pub neighboring_mines: u8,
+}
+
+/// The result of steppin on a spot in the minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StepResult {
+    /// Stepped on empty spot
+    Phew,
+
+    /// Stepped on a mine
+    Boom,
+
+    /// Step not taken
+    Invalid
+}
+
+/// Reasons why `Minefield::try_step` or `Minefield::try_auto_step` can't step on a spot
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StepError {
+    /// The given coordinates are outside the minefield, or a hole
+    OutOfBounds { x: Coord, y: Coord },
+
+    /// The spot is already revealed
+    AlreadyRevealed,
+
+    /// The spot carries a flag
+    Flagged,
+
+    /// The game is already won or lost
+    GameOver,
+
+    /// The minefield is locked
+    Locked,
+
+    /// The spot to auto step around is not a revealed count
+    NotRevealedCount,
+
+    /// The spot to auto step around doesn't have as many flagged neighbors as neighboring mines
+    FlagMismatch { flags: u8, neighboring_mines: u8 },
+}
+
+impl std::fmt::Display for StepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::OutOfBounds { x, y } => write!(f, "coordinates ({}, {}) are outside the minefield", x, y),
+            StepError::AlreadyRevealed => write!(f, "the spot is already revealed"),
+            StepError::Flagged => write!(f, "the spot is flagged"),
+            StepError::GameOver => write!(f, "the game is over"),
+            StepError::Locked => write!(f, "the minefield is locked"),
+            StepError::NotRevealedCount => write!(f, "the spot is not a revealed count"),
+            StepError::FlagMismatch { flags, neighboring_mines } => {
+                write!(f, "{} flags around a spot with {} neighboring mines", flags, neighboring_mines)
+            },
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+/// The result of toggling a flag in the mine field
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FlagToggleResult {
+    /// Exstng flag (or question mark) was removed, and the spot carries no mark
+    Removed,
+    /// A flag was added
+    Added,
+    /// No flag placed or removed
+    None,
+    /// No flag placed, because there are already as many flags as mines
+    LimitReached,
+    /// Existing flag was replaced by a question mark
+    Questioned,
+}
+
+/// Which marks toggling a flag cycles through
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum FlagMode {
+    /// No mark, then a flag
+    #[default]
+    Flags,
+
+    /// No mark, then a flag, then a question mark
+    FlagsAndQuestionMarks,
+}
+
+/// Receiver of the effects of the player actions on a minefield, e.g. to play sounds. All methods do nothing by
+/// default. They are called once an action is complete, in the order in which the spots changed (so a flood reveal
+/// tells about every spot it revealed). A listener has no access to the minefield it listens to, so it can't change
+/// it while an action is applied. Listeners are `Send + Sync`, so that minefields can be moved and shared between
+/// threads
+pub trait FieldListener: Send + Sync {
+    /// The spot at the given coordinates was revealed, showing the given count of neighboring mines
+    fn on_reveal(&mut self, _coords: (Coord, Coord), _neighboring_mines: u8) {}
+
+    /// A flag was added to (or removed from) the spot at the given coordinates
+    fn on_flag(&mut self, _coords: (Coord, Coord), _added: bool) {}
+
+    /// The mine at the given coordinates exploded
+    fn on_explode(&mut self, _coords: (Coord, Coord)) {}
+
+    /// The last safe spot of the minefield was revealed
+    fn on_cleared(&mut self) {}
+}
+
+/// Source of the current time for timing games (see `Minefield::with_clock`), e.g. to control time in tests, or on
+/// platforms where `Instant` is not available
+pub trait Clock {
+    /// Time since an arbitrary origin, which never goes backwards
+    fn now(&self) -> Duration;
+}
+
+/// The real clock, measuring the time since it was created with `Instant`
+#[derive(Copy, Clone, Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock whose origin is now
+    pub fn new() -> Self {
+        SystemClock { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// The generator of a minefield placing its mines adversarially, with the seed it started from
+#[cfg(feature = "adversarial")]
+#[derive(Clone, Debug)]
+struct Adversary {
+    /// Seed the generator started from
+    seed: u64,
+
+    /// Source of the random rearrangements
+    rng: StdRng,
+}
+
+/// Timer of the games played on a minefield. Clones share the same clock
+#[derive(Clone)]
+struct Timer {
+    /// Source of the current time
+    clock: Arc<dyn Clock + Send + Sync>,
+
+    /// Time spent playing before the timer last started running
+    elapsed: Duration,
+
+    /// Clock time when the timer started running, if it is running
+    running_since: Option<Duration>,
+
+    /// Whether the timer was paused
+    paused: bool,
+}
+
+impl Timer {
+    fn new(clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        Timer { clock, elapsed: Duration::ZERO, running_since: None, paused: false }
+    }
+
+    fn elapsed(&self) -> Duration {
+        let running = self.running_since.map_or(Duration::ZERO, |since| self.clock.now().saturating_sub(since));
+        self.elapsed + running
+    }
+}
+
+impl std::fmt::Debug for Timer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timer")
+            .field("elapsed", &self.elapsed)
+            .field("running_since", &self.running_since)
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+/// Optional listener of a minefield, which is not cloned with it
+struct Listener(Option<Box<dyn FieldListener>>);
+
+impl Clone for Listener {
+    fn clone(&self) -> Self {
+        Listener(None)
+    }
+}
+
+impl std::fmt::Debug for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(FieldListener)" } else { "None" })
+    }
+}
+
+/// An action taken by the player on a spot of the minefield
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Step on a spot
+    Step { x: Coord, y: Coord },
+
+    /// Automatically step on all hidden neighbors of a revealed spot
+    AutoStep { x: Coord, y: Coord },
+
+    /// Toggle the flag of a spot
+    ToggleFlag { x: Coord, y: Coord },
+
+    /// Flag or reveal the neighbors of a revealed spot, whichever its count allows
+    SmartChord { x: Coord, y: Coord },
+}
+
+/// The outcome of an `Action`, which depends on the kind of action
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ActionOutcome {
+    /// Outcome of a `Step`, `AutoStep` or `SmartChord` action
+    Step(StepResult),
+
+    /// Outcome of a `ToggleFlag` action
+    Flag(FlagToggleResult),
+}
+
+/// The result of applying an `Action` to the minefield
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ActionResult {
+    /// What the action did
+    pub outcome: ActionOutcome,
+
+    /// Coordinates of every spot revealed, exploded, flagged or unflagged by the action
+    pub changed: Vec<(Coord, Coord)>,
+}
+
+ #[cfg(test)]
+ mod tests {
+    use super::*;
+
+     #[test]
+     fn new_minefield() {
+        // Create empty test minefield:
+        //     0 1 2
+        // 0 [       ]
+        // 1 [       ]
+        // 2 [       ]
+        // 3 [       ]
+        //
+        let width = 3;
+        let height = 4;
+        let minefield = Minefield::new(width, height);
+
+        for ((x, y), spot) in minefield.spots() {
+            assert_eq!(spot.state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+            assert!(x < width);
+            assert!(y < height);
+        }
+     }
+
+     #[test]
+     fn try_new() {
+        let minefield = Minefield::try_new(9, 5).unwrap();
+        assert_eq!((minefield.width(), minefield.height()), (9, 5));
+
+        assert_eq!(Minefield::try_new(0, 5).unwrap_err(), SizeError::Empty);
+        assert_eq!(Minefield::try_new(5, 0).unwrap_err(), SizeError::Empty);
+        assert_eq!(Minefield::try_new(65535, 65535).unwrap_err(), SizeError::TooLarge { width: 65535, height: 65535 });
+        assert_eq!(
+            Minefield::builder().width(65535).height(65535).build().unwrap_err(),
+            BuildError::TooLarge { width: 65535, height: 65535 }
+        );
+        assert_eq!(Minefield::builder().width(0).height(5).build().unwrap_err(), BuildError::EmptySize);
+     }
+
+     #[test]
+     fn try_with_mines() {
+        let minefield = Minefield::new(4, 4).try_with_mines(16).unwrap();
+        assert_eq!(minefield.mines(), 16);
+
+        assert_eq!(
+            Minefield::new(4, 4).try_with_mines(17).unwrap_err(),
+            PlacementError::TooManyMines { mines: 17, max: 16 }
+        );
+
+        // Holes and reserved spots can't receive a mine, while mines already in place are replaced
+        let minefield = Minefield::with_shape(4, 4, |x, _y| x > 0).with_reserved_safe([(1, 1)]).with_mines(11);
+        assert_eq!(minefield.clone().try_with_mines(11).unwrap().mines(), 11);
+        assert_eq!(minefield.try_with_mines(12).unwrap_err(), PlacementError::TooManyMines { mines: 12, max: 11 });
+
+        // Unlike `try_with_mines`, `with_mines` places what it can
+        assert_eq!(Minefield::new(4, 4).with_mines(17).mines(), 16);
+     }
+
+     #[test]
+     fn reroll_mines() {
+        let minefield = Minefield::new(10, 10).with_mines(30).with_mines(20);
+        assert_eq!(minefield.mines(), 20);
+        assert_eq!(minefield.spots().filter(|(_coords, spot)| spot.is_mine()).count(), 20);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Seeded mines are the same whatever was there before
+        let rerolled = Minefield::new(10, 10).with_seeded_mines(60, 1).with_seeded_mines(20, 2);
+        assert_eq!(rerolled, Minefield::new(10, 10).with_seeded_mines(20, 2));
+        assert_eq!(rerolled.validate(), Ok(()));
+
+        let minefield = Minefield::new(10, 10).with_mines(90).with_symmetric_mines(10, Symmetry::Horizontal, 3);
+        assert_eq!(minefield.mines(), 10);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        let minefield = Minefield::new(8, 8).with_mines(60).with_mines_excluding_rect(48, (0, 0, 3, 3)).unwrap();
+        assert_eq!(minefield.mines(), 48);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Re-rolling a played minefield keeps the revealed spots, and fills at most the spots still hidden
+        let mut minefield = Minefield::new(10, 10).with_seeded_mines(10, 7);
+        minefield.step(0, 0);
+        minefield.step(9, 9);
+        let hidden = minefield.spots().filter(|(_coords, spot)| !spot.is_revealed()).count() as u32;
+        let revealed: Vec<(Coord, Coord)> =
+            minefield.spots().filter(|(_coords, spot)| spot.is_revealed()).map(|(coords, _spot)| coords).collect();
+
+        for mines in [5, hidden, hidden + 10, 100] {
+            let rerolled = minefield.clone().with_mines(mines);
+            assert_eq!(rerolled.mines(), mines.min(hidden));
+            assert!(revealed.iter().all(|&(x, y)| rerolled.spot(x, y).unwrap().is_revealed()));
+            assert_eq!(rerolled.validate(), Ok(()));
+        }
+     }
+
+     #[test]
+     fn count_mines() {
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!((minefield.mines(), minefield.count_mines()), (2, 2));
+
+        minefield.place_mine(1, 1).unwrap();
+        minefield.remove_mine(2, 0).unwrap();
+        assert_eq!(minefield.remove_mine(2, 0), Err(EditError::NotAMine { x: 2, y: 0 }));
+        assert_eq!((minefield.mines(), minefield.count_mines()), (2, 2));
+
+        let emptied = SpotState::HiddenEmpty { neighboring_mines: 0 };
+        assert_eq!(minefield.apply_diff(&[((2, 2), SpotState::HiddenMine), ((0, 3), emptied)]), Ok(()));
+        assert_eq!(minefield.apply_diff(&[((0, 0), SpotState::FlaggedMine)]), Ok(()));
+        assert_eq!((minefield.mines(), minefield.count_mines()), (3, 3));
+
+        let layout = Minefield::from_mine_layout(3, 3, &[true, false, true, false, true]);
+        assert_eq!((layout.mines(), layout.count_mines()), (3, 3));
+
+        let mut played = Minefield::new(8, 8).with_seeded_mines(10, 5).with_seeded_mines(12, 6);
+        assert_eq!((played.mines(), played.count_mines()), (12, 12));
+        played.step(0, 0);
+        played.finalize_loss();
+        let decoded = Minefield::from_bytes(&played.to_bytes()).unwrap();
+        assert_eq!((decoded.mines(), decoded.count_mines()), (12, 12));
+     }
+
+     #[test]
+     fn place_mines() {
+         // Create empty minefield
+        let width = 3;
+        let height = 4;
+        let mut minefield = Minefield::new(width, height);
+
+        // Place Mine
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [       ]
+        // 3 [       ]
+        //
+        let mine_x = 2;
+        let mine_y = 0;
+        minefield.place_mine(mine_x, mine_y).unwrap();
+
+        // Was mine placed correctly?
+        assert_eq!(minefield.spot(mine_x, mine_y).unwrap().state, SpotState::HiddenMine);
+
+        // Were the neighbors updated correctly?
+        for (nx, ny) in minefield.neighbors_coords(mine_x, mine_y) {
+            assert_eq!(minefield.spot(nx, ny).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        }
+
+        // Place another mine
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mine_x = 0;
+        let mine_y = 3;
+        minefield.place_mine(mine_x, mine_y).unwrap();
+
+        // Was mine placed correctly?
+        assert_eq!(minefield.spot(mine_x, mine_y).unwrap().state, SpotState::HiddenMine);
+
+        // Were the neighbors updated correctly?
+        for (nx, ny) in minefield.neighbors_coords(mine_x, mine_y) {
+            assert_eq!(minefield.spot(nx, ny).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        }
+
+        // Place another mine
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 2 1 ]
+        // 2 [ 2 2   ]
+        // 3 [ ☢ 1   ]
+        let mine_x = 0;
+        let mine_y = 1;
+        minefield.place_mine(mine_x, mine_y).unwrap();
+
+        // Was mine placed correctly?
+        assert_eq!(minefield.spot(mine_x, mine_y).unwrap().state, SpotState::HiddenMine);
+
+        // Were the neighbors updated correctly?
+        for n_coords in minefield.neighbors_coords(mine_x,  mine_y) {
+            let expected_mine_count = if n_coords == (0, 0) { 1 } else { 2 };
+            assert_eq!(
+                minefield.spot(n_coords.0, n_coords.1).unwrap().state, 
+                SpotState::HiddenEmpty { neighboring_mines: expected_mine_count }
+            );
+        }
+     }
+
+     #[test]
+     fn edit_mines() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::new(3, 4);
+        minefield.place_mine(2, 0).unwrap();
+        minefield.place_mine(0, 3).unwrap();
+        assert_eq!(minefield.mines(), 2);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Place then remove a mine leaves the minefield identical
+        let original = minefield.clone();
+        assert_eq!(minefield.place_mine(1, 1), Ok(()));
+        assert_eq!(minefield.mines(), 3);
+        assert_eq!(minefield.spot(0, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.validate(), Ok(()));
+        assert_eq!(minefield.remove_mine(1, 1), Ok(()));
+        assert_eq!(minefield, original);
+        assert_eq!(minefield.mines(), 2);
+
+        // Flags are kept while editing
+        minefield.toggle_flag(1, 1);
+        assert_eq!(minefield.place_mine(1, 1), Ok(()));
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.remove_mine(1, 1), Ok(()));
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::FlaggedEmpty { neighboring_mines: 1 });
+        minefield.toggle_flag(1, 1);
+        assert_eq!(minefield, original);
+
+        // Invalid edits
+        assert_eq!(minefield.place_mine(2, 0), Err(EditError::AlreadyMine { x: 2, y: 0 }));
+        assert_eq!(minefield.remove_mine(1, 0), Err(EditError::NotAMine { x: 1, y: 0 }));
+        assert_eq!(minefield.place_mine(3, 0), Err(EditError::OutOfBounds { x: 3, y: 0 }));
+        assert_eq!(minefield.remove_mine(0, 4), Err(EditError::OutOfBounds { x: 0, y: 4 }));
+        assert_eq!(minefield, original);
+
+        // Revealed and exploded spots can't be edited
+        minefield.step(1, 0);
+        minefield.step(2, 0);
+        assert_eq!(minefield.place_mine(1, 0), Err(EditError::Revealed { x: 1, y: 0 }));
+        assert_eq!(minefield.remove_mine(2, 0), Err(EditError::Revealed { x: 2, y: 0 }));
+        assert_eq!(minefield.mines(), 2);
+        assert_eq!(minefield.validate(), Ok(()));
+     }
+
+     #[test]
+     fn step() {
+         // Create empty minefield
+         let width = 3;
+         let height = 4;
+         let mut minefield = Minefield::new(width, height);
+
+        // Place mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mine_x = 2;
+        let mine_y = 0;
+        minefield.place_mine(mine_x, mine_y).unwrap();
+        let mine_x = 0;
+        let mine_y = 3;
+        minefield.place_mine(mine_x, mine_y).unwrap();
+
+        // Step on spot neighboring mine
+        let step_x = 1;
+        let step_y = 2;
+        let step_result = minefield.step(step_x, step_y);
+
+        // Step was success, and only one spot was revealed
+        //     0 1 2
+        // 0 [ • • • ]
+        // 1 [ • • • ]
+        // 2 [ • 1 • ]
+        // 3 [ • • • ]
+        assert_eq!(step_result, StepResult::Phew);
+        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Revealed);
+        // for (nx, ny) in minefield.neighbors_coords(step_x, step_y) {
+        //     assert_eq!(minefield.field[nx as usize][ny as usize].state, SpotState::Hidden);
+        // }
+
+        // Step on spot with no neighboring mines
+        let step_x = 0;
+        let step_y = 1;
+        let step_result = minefield.step(step_x, step_y);
+
+        // Step was success, and neighbors were flood revealed
+        //     0 1 2
+        // 0 [   1 • ]
+        // 1 [   1 • ]
+        // 2 [ 1 1 • ]
+        // 3 [ • • • ]
+        assert_eq!(step_result, StepResult::Phew);
+        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Revealed);
+        // for (nx, ny) in minefield.neighbors_coords(step_x, step_y) {
+        //     assert_eq!(minefield.field[nx as usize][ny as usize].state, SpotState::Revealed);
+        // }
+
+        // Step on mine
+        let step_x = 2;
+        let step_y = 0;
+        let step_result = minefield.step(step_x, step_y);
+
+        // Step was Boom, and only mine spot was newly revealed
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 • ]
+        // 2 [ 1 1 • ]
+        // 3 [ • • • ]
+        assert_eq!(step_result, StepResult::Boom);
+        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Exploded);
+        // for (x, y) in minefield.neighbors_coords(step_x,  step_y) {
+        //     let expected_spot_state= if (x, y) == (2, 1) { SpotState::Hidden } else { SpotState::Revealed };
+        //     assert_eq!(minefield.field[x as usize][y as usize].state, expected_spot_state);
+        // }
+     }
+
+     #[test]
+     fn assist_chord() {
+        //     0 1 2
+        // 0 [ 1 ☢ 1 ]
+        // 1 [ 1 1 1 ]
+        // 2 [       ]
+        let build = || {
+            let mut minefield = Minefield::from_fn(3, 3, |x, y| (x, y) == (1, 0));
+            assert_eq!(minefield.step(0, 2), StepResult::Phew);
+            minefield
+        };
+
+        // A misplaced flag makes the count look satisfied, so auto stepping hits the mine
+        let mut minefield = build();
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.auto_step(1, 1), StepResult::Boom);
+
+        // Assisted chording only steps on the neighbor proven safe, and leaves the flag alone
+        let mut minefield = build();
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.assist_chord(1, 1), (StepResult::Phew, vec![(2, 0)]));
+        assert!(minefield.spot(0, 0).unwrap().is_flagged());
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+        assert_eq!(minefield.assist_chord(1, 1), (StepResult::Invalid, vec![]));
+
+        // Without flags, every safe neighbor is stepped on
+        let mut minefield = build();
+        assert_eq!(minefield.assist_chord(1, 0), (StepResult::Invalid, vec![]));
+        assert_eq!(minefield.assist_chord(1, 1), (StepResult::Phew, vec![(0, 0), (2, 0)]));
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // A locked minefield is left untouched
+        let mut minefield = build();
+        minefield.lock();
+        assert_eq!(minefield.assist_chord(1, 1), (StepResult::Invalid, vec![]));
+        assert!(minefield.spot(0, 0).unwrap().is_hidden());
+        assert!(minefield.spot(2, 0).unwrap().is_hidden());
+     }
+
+     #[test]
+     fn try_step() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let build = || Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        let mut minefield = build();
+
+        // Hidden and questioned spots can be stepped on
+        assert_eq!(minefield.try_step(0, 0), Ok(StepResult::Phew));
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Questioned);
+        assert_eq!(minefield.try_step(2, 1), Ok(StepResult::Phew));
+
+        // Everything else is reported, without changing the minefield
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        let before = minefield.clone();
+        assert_eq!(minefield.try_step(3, 0), Err(StepError::OutOfBounds { x: 3, y: 0 }));
+        assert_eq!(minefield.try_step(1, 0), Err(StepError::AlreadyRevealed));
+        assert_eq!(minefield.try_step(2, 0), Err(StepError::Flagged));
+        assert_eq!(minefield, before);
+
+        // Auto steps around revealed counts with as many flags as mines
+        assert_eq!(minefield.try_auto_step(3, 3), Err(StepError::OutOfBounds { x: 3, y: 3 }));
+        assert_eq!(minefield.try_auto_step(2, 2), Err(StepError::NotRevealedCount));
+        assert_eq!(minefield.try_auto_step(2, 0), Err(StepError::NotRevealedCount));
+        assert_eq!(minefield.try_auto_step(0, 2), Err(StepError::FlagMismatch { flags: 0, neighboring_mines: 1 }));
+        assert_eq!(minefield, before);
+        assert_eq!(minefield.try_auto_step(2, 1), Ok(StepResult::Phew));
+        assert_eq!(minefield.spot(2, 2).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 0 });
+
+        // Once the game is won or lost, nothing more can be stepped on
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.try_auto_step(1, 1), Err(StepError::GameOver));
+
+        let mut minefield = build();
+        assert_eq!(minefield.try_step(0, 3), Ok(StepResult::Boom));
+        assert_eq!(minefield.try_step(0, 0), Err(StepError::GameOver));
+        assert_eq!(minefield.try_step(0, 3), Err(StepError::GameOver));
+     }
+
+     #[test]
+     fn take_dirty() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1 # ]
+        let mut minefield = Minefield::with_shape(3, 4, |x, y| (x, y) != (2, 3));
+        assert_eq!(minefield.place_mine(2, 0), Ok(()));
+        assert_eq!(minefield.place_mine(0, 3), Ok(()));
+
+        // Everything is new at first, and nothing changed since
+        assert_eq!(minefield.take_dirty().len(), 11);
+        assert_eq!(minefield.take_dirty(), vec![]);
+
+        // Flood reveals, flags and game over
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.take_dirty(), vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)]);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.take_dirty(), vec![(2, 0), (2, 2)]);
+        assert_eq!(minefield.step(2, 1), StepResult::Phew);
+        let checkpoint = minefield.checkpoint();
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        minefield.finalize_loss();
+        assert_eq!(minefield.take_dirty(), vec![(2, 1), (2, 2), (0, 3)]);
+
+        // Restoring a checkpoint puts back the spots which changed since
+        assert_eq!(minefield.restore(checkpoint), Ok(()));
+        assert_eq!(minefield.take_dirty(), vec![(2, 2), (0, 3)]);
+
+        // Starting over without mines changes every spot, since the counts drop to 0 too
+        minefield.regenerate(0, 0);
+        assert_eq!(minefield.take_dirty().len(), 11);
+        assert_eq!(minefield.take_dirty(), vec![]);
+     }
+
+     #[test]
+     fn step_as() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.owner_of(0, 0), None);
+
+        // Each player owns the opening they flood, and the spots revealed first stay with their owner
+        assert_eq!(minefield.step_as(1, 0, 0), StepResult::Phew);
+        assert_eq!(minefield.step_as(2, 2, 3), StepResult::Phew);
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)] {
+            assert_eq!(minefield.owner_of(x, y), Some(1));
+        }
+        for (x, y) in [(2, 1), (2, 2), (1, 3), (2, 3)] {
+            assert_eq!(minefield.owner_of(x, y), Some(2));
+        }
+
+        // Hidden spots, and spots revealed without a player, have no owner
+        assert_eq!(minefield.owner_of(2, 0), None);
+        assert_eq!(minefield.owner_of(3, 0), None);
+        assert_eq!(minefield.step_as(2, 1, 1), StepResult::Invalid);
+        assert_eq!(minefield.owner_of(1, 1), Some(1));
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        assert_eq!(minefield.owner_of(0, 3), None);
+
+        minefield.regenerate(2, 1);
+        assert_eq!(minefield.owner_of(0, 0), None);
+     }
+
+     #[test]
+     fn auto_finish_on_full_flag() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let build = || {
+            Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y))).auto_finish_on_full_flag(true)
+        };
+        let mut minefield = build();
+        minefield.enable_history();
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+
+        // A wrong flag holds back the finish, until it is removed
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Added);
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+        let result = minefield.apply(Action::ToggleFlag { x: 2, y: 2 });
+        assert_eq!(result.outcome, ActionOutcome::Flag(FlagToggleResult::Removed));
+
+        // The last action of the game is a flag toggle, which reveals the rest
+        assert_eq!(result.changed, vec![(2, 2), (2, 1), (1, 3), (2, 3)]);
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert!(minefield.is_cleared());
+
+        // Replaying the history finishes the game the same way
+        let mut replayed = build();
+        for timestamped in minefield.history() {
+            replayed.apply(timestamped.action);
+        }
+        assert_eq!(replayed, minefield);
+
+        // Off by default
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Added);
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+     }
+
+     #[test]
+     fn with_flood() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y))).with_flood(false);
+
+        // Only the spot stepped on is revealed, even without neighboring mines
+        let result = minefield.apply(Action::Step { x: 0, y: 0 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Phew));
+        assert_eq!(result.changed, vec![(0, 0)]);
+        assert_eq!(minefield.hidden_remaining(), 11);
+        assert_eq!(minefield.step(0, 0), StepResult::Invalid);
+        assert_eq!(minefield.hidden_remaining(), 11);
+
+        // Auto steps don't flood either
+        assert_eq!(minefield.step(0, 1), StepResult::Phew);
+        assert_eq!(minefield.auto_step(0, 1), StepResult::Phew);
+        assert_eq!(minefield.hidden_remaining(), 6);
+        assert!(minefield.spot(2, 2).unwrap().is_hidden());
+
+        // Flooding can still be asked for explicitly
+        assert_eq!(minefield.flood_from(0, 0), vec![]);
+        assert_eq!(minefield.flood_from(0, 1), vec![]);
+        assert_eq!(minefield.step(2, 2), StepResult::Phew);
+        assert_eq!(minefield.flood_from(2, 2), vec![(2, 1), (1, 3), (2, 3)]);
+     }
+
+     #[test]
+     fn timer() {
+        struct TestClock(Arc<std::sync::Mutex<Duration>>);
+
+        impl Clock for TestClock {
+            fn now(&self) -> Duration {
+                *self.0.lock().unwrap()
+            }
+        }
+
+        let time = Arc::new(std::sync::Mutex::new(Duration::from_secs(100)));
+        let wait = |seconds| *time.lock().unwrap() += Duration::from_secs(seconds);
+
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let build = || Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        let mut minefield = build().with_clock(TestClock(time.clone()));
+
+        // The timer starts with the first step which reveals something
+        wait(5);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.step(2, 0), StepResult::Invalid);
+        wait(5);
+        assert_eq!(minefield.elapsed(), Duration::ZERO);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        wait(3);
+        assert_eq!(minefield.elapsed(), Duration::from_secs(3));
+
+        // Pauses don't count
+        minefield.pause();
+        assert!(minefield.is_paused());
+        wait(10);
+        assert_eq!(minefield.elapsed(), Duration::from_secs(3));
+        minefield.resume();
+        assert!(!minefield.is_paused());
+        wait(2);
+        assert_eq!(minefield.elapsed(), Duration::from_secs(5));
+
+        // The timer stops once the game is over
+        assert_eq!(minefield.step(2, 2), StepResult::Phew);
+        assert_eq!(minefield.game_state(), GameState::Won);
+        wait(7);
+        assert_eq!(minefield.elapsed(), Duration::from_secs(5));
+        minefield.resume();
+        assert_eq!(minefield.elapsed(), Duration::from_secs(5));
+
+        // Starting over resets it
+        minefield.regenerate(2, 1);
+        assert_eq!(minefield.elapsed(), Duration::ZERO);
+
+        // Without a clock, nothing is timed
+        let mut minefield = build();
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.elapsed(), Duration::ZERO);
+     }
+
+     #[test]
+     fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Minefield>();
+        assert_send_sync::<Minefield<String>>();
+        assert_send_sync::<Timer>();
+     }
+
+     #[test]
+     fn lock() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let build = || Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        let mut minefield = build();
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+
+        minefield.lock();
+        assert!(minefield.is_locked());
+        let before = minefield.clone();
+        assert_eq!(minefield.step(2, 2), StepResult::Invalid);
+        assert_eq!(minefield.step(0, 3), StepResult::Invalid);
+        assert_eq!(minefield.auto_step(1, 1), StepResult::Invalid);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::None);
+        assert_eq!(minefield.try_step(2, 2), Err(StepError::Locked));
+        assert_eq!(minefield, before);
+        assert_eq!(minefield.move_count(), before.move_count());
+
+        minefield.unlock();
+        assert!(!minefield.is_locked());
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+
+        // Locks itself when the game is lost, keeping the final board
+        let mut minefield = build().with_lock_on_game_over(true);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert!(!minefield.is_locked());
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        assert!(minefield.is_locked());
+        assert_eq!(minefield.step(2, 2), StepResult::Invalid);
+        assert!(minefield.spot(2, 2).unwrap().is_hidden());
+
+        // Starting over unlocks it
+        minefield.regenerate(2, 1);
+        assert!(!minefield.is_locked());
+     }
+
+     #[test]
+     fn flood_reveal() {
+        // Create empty bigger minefield
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 ☢ 1           ]
+        // 1 [     1 1 1           ]
+        // 2 [           1 1 1     ]
+        // 3 [   1 1 1   1 ☢ 1 1 1 ]
+        // 4 [   1 ☢ 1   1 1 1 1 ☢ ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 ☢ 2 ☢ 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        let width = 10;
+        let height = 10;
+        let mut minefield = Minefield::new(width, height);
+
+        let mine_coords = [(2, 4), (5, 7), (7, 7), (9, 4), (6, 3), (3, 0)];
+        for (x, y) in mine_coords {
+            minefield.place_mine(x, y).unwrap();
+        }
+
+        // Place a flag
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [ • • • • • • • • • • ]
+        // 1 [ • • • • • ⚐ • • • • ]
+        // 2 [ • • • • • • • • • • ]
+        // 3 [ • • • • • • • • • • ]
+        // 4 [ • • • • • • • • • • ]
+        // 5 [ • • • • • • • • • • ]
+        // 6 [ • • • • • • • • • • ]
+        // 7 [ • • • • • • • • • • ]
+        // 8 [ • • • • • • • • • • ]
+        // 9 [ • • • • • • • • • • ]
+        let flag_x = 5;
+        let flag_y = 1;
+        let toggle_result = minefield.toggle_flag(flag_x, flag_y);
+        assert_eq!(toggle_result, FlagToggleResult::Added);
+
+        // Step on spot (x=9, y=6)
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 • • • • • • • ]
+        // 1 [     1 1 1 ⚐ • • • • ]
+        // 2 [           1 • • • • ]
+        // 3 [   1 1 1   1 • • • • ]
+        // 4 [   1 • 1   1 1 1 1 • ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 • • • 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        let step_x = 9;
+        let step_y = 6;
+        let step_result = minefield.step(step_x, step_y);
+        assert_eq!(step_result, StepResult::Phew);
+
+        // All mines are still hidden
+        for n_coords in mine_coords {
+            assert_eq!(minefield.spot(n_coords.0, n_coords.1).unwrap().state, SpotState::HiddenMine);
+        }
+
+        // Flood revealed half maze
+        assert_eq!(minefield.spot(7, 5).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 0 });
+
+        // Flag is still there
+        assert_eq!(
+            minefield.spot(flag_x, flag_y).unwrap().state, 
+            SpotState::FlaggedEmpty { neighboring_mines: 0 }
+        );
+
+        // Insulated portion of field is still hidden
+        assert_eq!(minefield.spot(9, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        assert_eq!(minefield.spot(7, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+     }
+
+     #[test]
+     fn flag_accuracy() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::new(3, 4);
+        minefield.place_mine(2, 0).unwrap();
+        minefield.place_mine(0, 3).unwrap();
+
+        // No flags placed yet
+        assert_eq!(minefield.flag_accuracy(), (0, 0));
+
+        // Flag both mines, and two empty spots
+        //     0 1 2
+        // 0 [ ⚐ • ⚐ ]
+        // 1 [ • • • ]
+        // 2 [ • • • ]
+        // 3 [ ⚐ ⚐ • ]
+        for (x, y) in [(2, 0), (0, 3), (0, 0), (1, 3)] {
+            assert_eq!(minefield.toggle_flag(x, y), FlagToggleResult::Added);
+        }
+        assert_eq!(minefield.flag_accuracy(), (2, 2));
+
+        // Remove one correct flag
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield.flag_accuracy(), (1, 2));
+     }
+
+     #[test]
+     fn state_flat() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1 # ]
+        let mut minefield = Minefield::with_shape(3, 4, |x, y| (x, y) != (2, 3));
+        assert_eq!(minefield.place_mine(2, 0), Ok(()));
+        assert_eq!(minefield.place_mine(0, 3), Ok(()));
+        assert_eq!(minefield.state_flat(), vec![9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 15]);
+
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Questioned);
+        assert_eq!(minefield.toggle_flag(2, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.state_flat(), vec![0, 1, 9, 0, 1, 11, 1, 1, 10, 9, 9, 15]);
+
+        // Mines and wrong flags are shown after a loss
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        minefield.finalize_loss();
+        assert_eq!(minefield.state_flat(), vec![0, 1, 12, 0, 1, 11, 1, 1, 14, 13, 9, 15]);
+     }
+
+     #[test]
+     fn to_grid() {
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 2 1 ]
+        let mut minefield = Minefield::with_shape(3, 2, |x, y| (x, y) != (2, 1));
+        for (x, y) in [(2, 0), (0, 1)] {
+            minefield.place_mine(x, y).unwrap();
+        }
+        minefield.toggle_flag(0, 1);
+        minefield.step(1, 1);
+
+        let grid = minefield.to_grid();
+        assert_eq!(grid.len(), 2);
+        assert!(grid.iter().all(|row| row.len() == 3));
+        assert_eq!(grid[0][2], Some(SpotState::HiddenMine));
+        assert_eq!(grid[1][0], Some(SpotState::FlaggedMine));
+        assert_eq!(grid[1][1], Some(SpotState::RevealedEmpty { neighboring_mines: 2 }));
+        assert_eq!(grid[1][2], None);
+
+        // Same orientation as `spot(x, y)`
+        for ((x, y), spot) in minefield.spots() {
+            assert_eq!(grid[y as usize][x as usize], Some(spot.state));
+        }
+
+        let visible = minefield.to_grid_visible();
+        assert_eq!(visible[0][2], Some(SpotState::HiddenEmpty { neighboring_mines: 0 }));
+        assert_eq!(visible[1][0], Some(SpotState::FlaggedEmpty { neighboring_mines: 0 }));
+        assert_eq!(visible[1][1], grid[1][1]);
+        assert_eq!(visible[1][2], None);
+     }
+
+     #[test]
+     fn map_grid() {
+        let mut minefield = Minefield::with_shape(3, 4, |x, y| (x, y) != (2, 3));
+        for (x, y) in [(2, 0), (0, 3)] {
+            minefield.place_mine(x, y).unwrap();
+        }
+        minefield.step(0, 0);
+        minefield.toggle_flag(2, 0);
+
+        let chars = minefield.map_grid(|_coords, spot| spot.map_or('#', |spot| spot.glyph()));
+        let rows: Vec<String> = chars.iter().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, minefield.render().lines().collect::<Vec<_>>());
+        assert_eq!(rows, vec![" 1F", " 1.", "11.", "..#"]);
+
+        let coords = minefield.map_grid(|coords, _spot| coords);
+        assert_eq!(coords[3][1], (1, 3));
+     }
+
+     #[test]
+     fn player_view() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 2 1 ]
+        // 2 [ 2 2   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::new(3, 4);
+        for (x, y) in [(2, 0), (0, 3), (0, 1)] {
+            minefield.place_mine(x, y).unwrap();
+        }
+
+        // Flag a mine, flag an empty spot, and reveal a spot
+        //     0 1 2
+        // 0 [ • • • ]
+        // 1 [ ⚐ • • ]
+        // 2 [ • • • ]
+        // 3 [ • 1 ⚐ ]
+        minefield.toggle_flag(0, 1);
+        minefield.toggle_flag(2, 3);
+        minefield.step(1, 3);
+
+        let view = minefield.player_view();
+
+        // No mines are visible to the player
+        for (_spot_coords, spot) in view.spots() {
+            assert_ne!(spot.state, SpotState::HiddenMine);
+            assert_ne!(spot.state, SpotState::FlaggedMine);
+        }
+
+        // Hidden and flagged mines show their true count of neighboring mines
+        assert_eq!(view.spot(2, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        assert_eq!(view.spot(0, 3).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        assert_eq!(view.spot(0, 1).unwrap().state, SpotState::FlaggedEmpty { neighboring_mines: 0 });
+
+        // Everything else is left as-is
+        assert_eq!(view.spot(2, 3).unwrap().state, SpotState::FlaggedEmpty { neighboring_mines: 0 });
+        assert_eq!(view.spot(1, 3).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 1 });
+        assert_eq!(view.spot(1, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 2 });
+        assert_eq!(view.mines(), minefield.mines());
+     }
+
+     #[test]
+     fn from_fn() {
+        // Create a checkerboard of mines
+        //     0 1 2 3
+        // 0 [ ☢ 3 ☢ 2 ]
+        // 1 [ 3 ☢ 4 ☢ ]
+        // 2 [ ☢ 3 ☢ 2 ]
+        let minefield = Minefield::from_fn(4, 3, |x, y| (x + y) % 2 == 0);
+        assert_eq!(minefield.mines(), 6);
+
+        // Same layout, with the mines placed one by one
+        let mut expected = Minefield::new(4, 3);
+        for (x, y) in [(0, 0), (2, 0), (1, 1), (3, 1), (0, 2), (2, 2)] {
+            expected.place_mine(x, y).unwrap();
+        }
+
+        for ((x, y), spot) in minefield.spots() {
+            assert_eq!(spot, expected.spot(x, y).unwrap());
+        }
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::HiddenMine);
+        assert_eq!(minefield.spot(1, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 3 });
+        assert_eq!(minefield.spot(2, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 4 });
+        assert_eq!(minefield.spot(3, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 2 });
+
+        // No mines at all
+        let minefield = Minefield::from_fn(4, 3, |_x, _y| false);
+        assert_eq!(minefield.mines(), 0);
+        assert!(minefield.spots().all(|(_spot_coords, spot)| spot == Spot::default()));
+     }
+
+     #[test]
+     fn validate() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 2 1 ]
+        // 2 [ 2 2   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3), (0, 1)].contains(&(x, y)));
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Random minefields are valid too
+        assert_eq!(Minefield::new(10, 10).with_mines(30).validate(), Ok(()));
+
+        // Playing doesn't break the minefield
+        minefield.toggle_flag(0, 1);
+        minefield.step(1, 1);
+        minefield.step(2, 0);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Corrupt a neighbor count
+        let index = minefield.index(1, 2).unwrap();
+        minefield.update(index, |spot| spot.state = SpotState::HiddenEmpty { neighboring_mines: 1 });
+        assert_eq!(
+            minefield.validate(),
+            Err(vec![ValidationError::NeighborCount { x: 1, y: 2, stored: 1, actual: 2 }])
+        );
+        minefield.update(index, |spot| spot.state = SpotState::HiddenEmpty { neighboring_mines: 2 });
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Corrupt the mine count
+        minefield.mines = 2;
+        assert_eq!(minefield.validate(), Err(vec![ValidationError::MineCount { stored: 2, actual: 3 }]));
+        minefield.mines = 3;
+
+        // All inconsistencies are reported
+        let index = minefield.index(2, 3).unwrap();
+        minefield.update(index, |spot| spot.state = SpotState::HiddenMine);
+        assert_eq!(
+            minefield.validate(),
+            Err(vec![
+                ValidationError::NeighborCount { x: 1, y: 2, stored: 2, actual: 3 },
+                ValidationError::NeighborCount { x: 2, y: 2, stored: 0, actual: 1 },
+                ValidationError::NeighborCount { x: 1, y: 3, stored: 1, actual: 2 },
+                ValidationError::MineCount { stored: 3, actual: 4 },
+            ])
+        );
+        minefield.update(index, |spot| spot.state = SpotState::HiddenEmpty { neighboring_mines: 0 });
+
+        // Lose a spot
+        minefield.field.pop();
+        assert_eq!(minefield.validate(), Err(vec![ValidationError::SpotCount { stored: 11, actual: 12 }]));
+     }
+
+     #[test]
+     fn recompute_counts() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 2 1 ]
+        // 2 [ 2 2   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3), (0, 1)].contains(&(x, y)));
+        minefield.toggle_flag(1, 1);
+        minefield.step(1, 3);
+
+        // Corrupt the counts of a hidden, a flagged and a revealed spot
+        for ((x, y), state) in [
+            ((0, 0), SpotState::HiddenEmpty { neighboring_mines: 5 }),
+            ((1, 1), SpotState::FlaggedEmpty { neighboring_mines: 0 }),
+            ((1, 3), SpotState::RevealedEmpty { neighboring_mines: 8 }),
+        ] {
+            let index = minefield.index(x, y).unwrap();
+            minefield.update(index, |spot| spot.state = state);
+        }
+        assert!(minefield.validate().is_err());
+
+        minefield.recompute_counts();
+        assert_eq!(minefield.validate(), Ok(()));
+        assert_eq!(minefield.spot(0, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::FlaggedEmpty { neighboring_mines: 2 });
+        assert_eq!(minefield.spot(1, 3).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 1 });
+     }
+
+     #[test]
+     fn unflagged_mine_count() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ 1 2 ☢ ]
+        // 1 [ ☢ 3 2 ]
+        // 2 [ 2 ☢ 1 ]
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| [(2, 0), (0, 1), (1, 2)].contains(&(x, y)));
+        minefield.step(1, 1);
+        assert_eq!(minefield.unflagged_mine_count(1, 1), Some(3));
+
+        // Flag a mine, and a spot without a mine
+        //     0 1 2
+        // 0 [ ⚐ • ⚐ ]
+        // 1 [ • 3 • ]
+        // 2 [ • • • ]
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(0, 0);
+        assert_eq!(minefield.unflagged_mine_count(1, 1), Some(1));
+
+        // More flags than mines
+        minefield.toggle_flag(2, 2);
+        minefield.toggle_flag(2, 1);
+        assert_eq!(minefield.unflagged_mine_count(1, 1), Some(0));
+
+        // Only revealed empty spots have a count
+        assert_eq!(minefield.unflagged_mine_count(1, 0), None);
+        assert_eq!(minefield.unflagged_mine_count(0, 0), None);
+        assert_eq!(minefield.unflagged_mine_count(0, 1), None);
+        assert_eq!(minefield.unflagged_mine_count(3, 3), None);
+     }
+
+     #[test]
+     fn limit_flags_to_mines() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+
+        // Unlimited by default
+        for (x, y) in [(0, 0), (1, 0), (2, 0)] {
+            assert_eq!(minefield.toggle_flag(x, y), FlagToggleResult::Added);
+        }
+        assert_eq!(minefield.flags_placed(), 3);
+
+        // Limited to the number of mines
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)))
+            .limit_flags_to_mines(true);
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.flags_placed(), 2);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::LimitReached);
+        assert_eq!(minefield.spot(0, 3).unwrap().state, SpotState::HiddenMine);
+        assert_eq!(minefield.flags_placed(), 2);
+
+        // Flags can always be removed, which makes room for a new one
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield.flags_placed(), 1);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Added);
+        assert_eq!(minefield.flags_placed(), 2);
+
+        // Revealed spots still can't be flagged
+        minefield.step(1, 1);
+        assert_eq!(minefield.toggle_flag(1, 1), FlagToggleResult::None);
+        assert_eq!(minefield.validate(), Ok(()));
+     }
+
+     #[test]
+     fn flag_remaining_mines() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+
+        // Nothing happens while empty spots are still hidden
+        minefield.step(0, 0);
+        assert_eq!(minefield.flag_remaining_mines(), 0);
+        assert_eq!(minefield.flags_placed(), 0);
+
+        // Reveal the remaining empty spots, and flag one of the mines by hand
+        minefield.step(2, 2);
+        minefield.step(1, 3);
+        minefield.toggle_flag(2, 0);
+        assert!(!minefield.is_cleared());
+
+        // Only the hidden mine gets flagged
+        assert_eq!(minefield.flag_remaining_mines(), 1);
+        assert_eq!(minefield.flags_placed(), 2);
+        assert_eq!(minefield.spot(0, 3).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.flag_accuracy(), (2, 0));
+        assert!(minefield.is_cleared());
+
+        // Calling again has nothing left to flag
+        assert_eq!(minefield.flag_remaining_mines(), 0);
+     }
+
+     #[test]
+     fn flag_all_mines() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let build = || Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        let mut minefield = build();
+
+        // Nothing happens while the game is in progress
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.flag_all_mines(), vec![]);
+        assert_eq!(minefield.flags_placed(), 0);
+
+        // Once won, every remaining mine is flagged
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Questioned);
+        assert_eq!(minefield.step(2, 2), StepResult::Phew);
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert!(!minefield.is_cleared());
+        let moves = minefield.move_count();
+        minefield.lock();
+        assert_eq!(minefield.flag_all_mines(), vec![(2, 0), (0, 3)]);
+        assert_eq!(minefield.move_count(), moves);
+        assert_eq!(minefield.flags_placed(), 2);
+        assert_eq!(minefield.safe_spots_remaining(), 0);
+        assert!(minefield.is_cleared());
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.flag_all_mines(), vec![]);
+
+        // Nothing happens once lost
+        let mut minefield = build();
+        assert_eq!(minefield.step(2, 0), StepResult::Boom);
+        assert_eq!(minefield.flag_all_mines(), vec![]);
+        assert_eq!(minefield.flags_placed(), 0);
+     }
+
+     #[test]
+     fn large_flood_reveal() {
+        // Sparse 500x500 minefield, with a large cavern around the center
+        let minefield = Minefield::new(500, 500).with_seeded_mines(2_000, 7);
+        let (x, y) = minefield
+            .spots()
+            .map(|(coords, _spot)| coords)
+            .filter(|&(x, y)| minefield.spot(x, y).unwrap().state == SpotState::HiddenEmpty { neighboring_mines: 0 })
+            .min_by_key(|&(x, y)| (x as i32 - 250).abs() + (y as i32 - 250).abs())
+            .unwrap();
+
+        // Straightforward reference flood reveal, working on coordinates
+        let mut expected = HashSet::from([(x, y)]);
+        let mut spots_to_visit = vec![(x, y)];
+
+        while let Some((xx, yy)) = spots_to_visit.pop() {
+            for ((nx, ny), spot) in minefield.neighbors(xx, yy) {
+                if let SpotState::HiddenEmpty { neighboring_mines } = spot.state {
+                    if expected.insert((nx, ny)) && neighboring_mines == 0 {
+                        spots_to_visit.push((nx, ny));
+                    }
+                }
+            }
+        }
+
+        assert!(expected.len() > 10_000);
+
+        let mut flooded = minefield.clone();
+        let start = Instant::now();
+        assert_eq!(flooded.step(x, y), StepResult::Phew);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let revealed: HashSet<(Coord, Coord)> = flooded
+            .spots()
+            .filter(|(_coords, spot)| spot.is_revealed())
+            .map(|(coords, _spot)| coords)
+            .collect();
+        assert_eq!(revealed, expected);
+
+        // Each spot is reported as changed exactly once
+        let changed = minefield.clone().apply(Action::Step { x, y }).changed;
+        assert_eq!(changed.len(), expected.len());
+        assert_eq!(changed.into_iter().collect::<HashSet<_>>(), expected);
+     }
+
+     #[test]
+     fn spots_ordered() {
+        let minefield = Minefield::new(3, 2).with_seeded_mines(2, 1);
+        let coords: Vec<(Coord, Coord)> = minefield.spots_ordered().map(|(coords, _spot)| coords).collect();
+        assert_eq!(coords, vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+
+        // Holes are skipped, without disturbing the order
+        let minefield = Minefield::with_shape(3, 2, |x, y| (x, y) != (1, 0));
+        let coords: Vec<(Coord, Coord)> = minefield.spots_ordered().map(|(coords, _spot)| coords).collect();
+        assert_eq!(coords, vec![(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+     }
+
+     #[test]
+     fn exploded_at() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.exploded_at(), None);
+
+        // Flag the wrong spot, then auto step around it
+        minefield.step(1, 1);
+        minefield.toggle_flag(2, 1);
+        assert_eq!(minefield.exploded_at(), None);
+        assert_eq!(minefield.auto_step(1, 1), StepResult::Boom);
+        assert_eq!(minefield.exploded_at(), Some((2, 0)));
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::ExplodedMine);
+
+        // The most recent explosion is reported
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        assert_eq!(minefield.exploded_at(), Some((0, 3)));
+
+        // Decoding loses the order of the explosions
+        let decoded = Minefield::from_bytes(&minefield.to_bytes()).unwrap();
+        assert_eq!(decoded.exploded_at(), Some((2, 0)));
+     }
+
+     #[test]
+     fn exploded_mines() {
+        //  . 1 *
+        //  . 1 F   (wrong flag)
+        //  1 1 .
+        //  * 1 .
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.exploded_mines(), vec![]);
+
+        assert_eq!(minefield.step(1, 1), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.auto_step(1, 1), StepResult::Boom);
+        assert_eq!(minefield.exploded_mines(), vec![(2, 0)]);
+
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        assert_eq!(minefield.exploded_mines(), vec![(2, 0), (0, 3)]);
+
+        // Mines shown after the loss didn't explode
+        minefield.finalize_loss();
+        assert_eq!(minefield.exploded_mines(), vec![(2, 0), (0, 3)]);
+     }
+
+     #[test]
+     fn index_of() {
+        let minefield = Minefield::with_shape(4, 3, |x, y| (x, y) != (3, 2));
+
+        for (x, y, index) in [(0, 0, 0), (3, 0, 3), (0, 2, 8), (3, 2, 11)] {
+            assert_eq!(minefield.index_of(x, y), Some(index));
+            assert_eq!(minefield.coords_of(index), Some((x, y)));
+        }
+
+        assert_eq!(minefield.index_of(4, 0), None);
+        assert_eq!(minefield.index_of(0, 3), None);
+        assert_eq!(minefield.coords_of(12), None);
+
+        // Every spot round-trips through its index
+        for ((x, y), _spot) in minefield.spots() {
+            assert_eq!(minefield.coords_of(minefield.index_of(x, y).unwrap()), Some((x, y)));
+        }
+     }
+
+     #[test]
+     fn block_coords() {
+        let minefield = Minefield::new(4, 3);
+
+        let block: Vec<_> = minefield.block_coords(1, 1).collect();
+        assert_eq!(block.len(), 9);
+        assert_eq!(block[0], (0, 0));
+        assert_eq!(block[4], (1, 1));
+        assert_eq!(block[8], (2, 2));
+
+        assert_eq!(minefield.block_coords(0, 0).collect::<Vec<_>>(), vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(minefield.block_coords(3, 2).collect::<Vec<_>>(), vec![(2, 1), (3, 1), (2, 2), (3, 2)]);
+
+        // Holes are skipped
+        let holed = Minefield::with_shape(3, 3, |x, y| (x, y) != (1, 1));
+        assert_eq!(holed.block_coords(1, 1).count(), 8);
+        assert!(!holed.block_coords(1, 1).any(|coords| coords == (1, 1)));
+     }
+
+     #[test]
+     fn neighbor_summary() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(0, 0);
+        minefield.toggle_flag(2, 1);
+        minefield.step(2, 0);
+
+        // Player board
+        //     0 1 2
+        // 0 [   1 * ]
+        // 1 [   1 ⚐ ]
+        // 2 [ 1 1 # ]
+        // 3 [ # # # ]
+        assert_eq!(
+            minefield.neighbor_summary(1, 1),
+            NeighborSummary { hidden: 1, flagged: 1, revealed: 6, mines_shown: 1 }
+        );
+        assert_eq!(
+            minefield.neighbor_summary(0, 3),
+            NeighborSummary { hidden: 1, flagged: 0, revealed: 2, mines_shown: 0 }
+        );
+        assert_eq!(minefield.neighbor_summary(3, 0), NeighborSummary::default());
+     }
+
+     #[test]
+     fn finalize_loss() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(0, 0);
+        minefield.toggle_flag(2, 1);
+        minefield.toggle_flag(0, 3);
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+
+        // Nothing happens while the game is in progress
+        let in_progress = minefield.clone();
+        minefield.finalize_loss();
+        assert_eq!(minefield, in_progress);
+
+        assert_eq!(minefield.step(2, 0), StepResult::Boom);
+        assert_eq!(minefield.game_state(), GameState::Lost);
+        minefield.finalize_loss();
+
+        // Post-mortem board
+        //     0 1 2
+        // 0 [   1 💥 ]
+        // 1 [   1 ⨯ ]
+        // 2 [ 1 1 • ]
+        // 3 [ ⚐ • • ]
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::ExplodedMine);
+        assert_eq!(minefield.spot(2, 1).unwrap().state, SpotState::WrongFlag { neighboring_mines: 1 });
+        assert_eq!(minefield.spot(0, 3).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.spot(2, 2).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        assert_eq!(minefield.flags_placed(), 2);
+        assert!(!minefield.is_cleared());
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Idempotent
+        let finalized = minefield.clone();
+        minefield.finalize_loss();
+        assert_eq!(minefield, finalized);
+
+        // Hidden mines are shown
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(0, 3);
+        minefield.finalize_loss();
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::RevealedMine);
+        assert_eq!(minefield.mines(), 2);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Won games are left as they are
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(0, 0);
+        minefield.step(2, 2);
+        minefield.step(1, 3);
+        assert_eq!(minefield.game_state(), GameState::Won);
+        minefield.finalize_loss();
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::HiddenMine);
+     }
+
+     #[test]
+     fn hint() {
+        // Create minefield with mines
+        //     0 1 2 3 4
+        // 0 [   1 ☢ 1   ]
+        let mut minefield = Minefield::from_fn(5, 1, |x, _y| x == 2);
+        minefield.step(0, 0);
+        minefield.step(3, 0);
+
+        // The mine is certain from the count at (1, 0), so the count at (3, 0) proves (4, 0) is safe. It can't be
+        // stepped on while flagged, and the only other hidden spot is certainly a mine
+        minefield.toggle_flag(4, 0);
+        assert_eq!(minefield.hint(), None);
+        minefield.toggle_flag(4, 0);
+        assert_eq!(minefield.hint(), Some(Hint::Safe { x: 4, y: 0 }));
+        assert!(minefield.spot(4, 0).unwrap().is_revealed());
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.hint(), None);
+
+        // Create minefield with mines
+        //     0 1
+        // 0 [ 1 1 ]
+        // 1 [ 1 ☢ ]
+        let mut minefield = Minefield::from_fn(2, 2, |x, y| (x, y) == (1, 1));
+        minefield.step(0, 0);
+
+        // Nothing is provable, every hidden spot is equally likely to be the mine
+        assert_eq!(minefield.hint(), Some(Hint::Guess { x: 1, y: 0, result: StepResult::Phew }));
+        assert_eq!(minefield.hint(), Some(Hint::Guess { x: 0, y: 1, result: StepResult::Phew }));
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.hint(), None);
+     }
+
+     #[test]
+     fn hidden_regions() {
+        // Create minefield with mines
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 ☢ 1           ]
+        // 1 [     1 1 1           ]
+        // 2 [           1 1 1     ]
+        // 3 [   1 1 1   1 ☢ 1 1 1 ]
+        // 4 [   1 ☢ 1   1 1 1 1 ☢ ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 ☢ 2 ☢ 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        let mine_coords = [(2, 4), (5, 7), (7, 7), (9, 4), (6, 3), (3, 0)];
+        let mut minefield = Minefield::from_fn(10, 10, |x, y| mine_coords.contains(&(x, y)));
+        assert_eq!(minefield.hidden_regions().len(), 1);
+        assert_eq!(minefield.hidden_region_count(), 1);
+
+        // Flag and step like in the `flood_reveal` test, which splits the hidden spots into three regions
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 a a a a a a a ]
+        // 1 [     1 1 1 ⚐ a a a a ]
+        // 2 [           1 a a a a ]
+        // 3 [   1 1 1   1 a a a a ]
+        // 4 [   1 b 1   1 1 1 1 a ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 c c c 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        minefield.toggle_flag(5, 1);
+        minefield.step(9, 6);
+
+        let regions = minefield.hidden_regions();
+        assert_eq!(regions.iter().map(Vec::len).collect::<Vec<usize>>(), vec![21, 1, 3]);
+        assert!(regions[0].contains(&(5, 1)));
+        assert_eq!(regions[1], vec![(2, 4)]);
+        let region: HashSet<(Coord, Coord)> = regions[2].iter().copied().collect();
+        assert_eq!(region, [(5, 7), (6, 7), (7, 7)].into_iter().collect());
+        assert_eq!(minefield.hidden_region_count(), 3);
+
+        // No hidden spots left, no regions
+        let mut minefield = Minefield::new(3, 3);
+        minefield.step(0, 0);
+        assert_eq!(minefield.hidden_regions(), Vec::<Vec<(Coord, Coord)>>::new());
+        assert_eq!(minefield.hidden_region_count(), 0);
+     }
+
+     #[test]
+     fn hidden_islands() {
+        // Create minefield with mines
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 ☢ 1           ]
+        // 1 [     1 1 1           ]
+        // 2 [           1 1 1     ]
+        // 3 [   1 1 1   1 ☢ 1 1 1 ]
+        // 4 [   1 ☢ 1   1 1 1 1 ☢ ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 ☢ 2 ☢ 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        let mine_coords = [(2, 4), (5, 7), (7, 7), (9, 4), (6, 3), (3, 0)];
+        let mut minefield = Minefield::from_fn(10, 10, |x, y| mine_coords.contains(&(x, y)));
+
+        // Everything is a single island before the first step
+        assert_eq!(minefield.hidden_islands().len(), 1);
+        assert_eq!(minefield.hidden_islands()[0].len(), 100);
+
+        // Flag and step like in the `flood_reveal` test, which leaves an insulated pocket (marked with `o`) in the top
+        // right corner
+        //     0 1 2 3 4 5 6 7 8 9
+        // 0 [     1 • • • o o o o ]
+        // 1 [     1 1 1 ⚐ • o o o ]
+        // 2 [           1 • o o o ]
+        // 3 [   1 1 1   1 • • • • ]
+        // 4 [   1 • 1   1 1 1 1 • ]
+        // 5 [   1 1 1         1 1 ]
+        // 6 [         1 1 2 1 1   ]
+        // 7 [         1 • • • 1   ]
+        // 8 [         1 1 2 1 1   ]
+        // 9 [                     ]
+        minefield.toggle_flag(5, 1);
+        minefield.step(9, 6);
+
+        let islands = minefield.hidden_islands();
+        assert_eq!(islands.len(), 1);
+
+        let island: HashSet<(Coord, Coord)> = islands[0].iter().copied().collect();
+        let expected: HashSet<(Coord, Coord)> = [
+            (6, 0), (7, 0), (8, 0), (9, 0),
+            (7, 1), (8, 1), (9, 1),
+            (7, 2), (8, 2), (9, 2),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(island, expected);
+     }
+
+     #[test]
+     fn zero_cells() {
+        //     0 1 2 3
+        // 0 [   1 ☢ 1 ]
+        // 1 [   1 1 1 ]
+        // 2 [ 1 1     ]
+        // 3 [ ☢ 1   # ]
+        let mut minefield = Minefield::with_shape(4, 4, |x, y| (x, y) != (3, 3));
+        assert_eq!(minefield.place_mine(2, 0), Ok(()));
+        assert_eq!(minefield.place_mine(0, 3), Ok(()));
+        let zeroes = vec![(0, 0), (0, 1), (2, 2), (3, 2), (2, 3)];
+        assert_eq!(minefield.zero_cells(), zeroes);
+
+        // Revealing spots doesn't change them
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.zero_cells(), zeroes);
+
+        assert_eq!(Minefield::new(2, 1).zero_cells(), vec![(0, 0), (1, 0)]);
+        assert_eq!(Minefield::from_fn(2, 1, |x, _y| x == 0).zero_cells(), vec![]);
+     }
+
+     #[test]
+     fn cells_adjacent_to_mines() {
+        //     0 1 2 3
+        // 0 [   1 ☢ 1 ]
+        // 1 [   1 1 1 ]
+        // 2 [ 1 1     ]
+        // 3 [ ☢ 1   # ]
+        let mut minefield = Minefield::with_shape(4, 4, |x, y| (x, y) != (3, 3));
+        assert_eq!(minefield.place_mine(2, 0), Ok(()));
+        assert_eq!(minefield.place_mine(0, 3), Ok(()));
+        let numbered: HashSet<(Coord, Coord)> =
+            [(1, 0), (3, 0), (1, 1), (2, 1), (3, 1), (0, 2), (1, 2), (1, 3)].into_iter().collect();
+        assert_eq!(minefield.cells_adjacent_to_mines(), numbered);
+
+        // Revealing spots doesn't change them
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.cells_adjacent_to_mines(), numbered);
+
+        assert_eq!(Minefield::new(3, 3).cells_adjacent_to_mines(), HashSet::new());
+     }
+
+     #[test]
+     fn count_distribution() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.count_distribution(), [4, 6, 0, 0, 0, 0, 0, 0, 0]);
+
+        //     0 1 2
+        // 0 [ ☢ ☢ ☢ ]
+        // 1 [ ☢ 8 ☢ ]
+        // 2 [ ☢ ☢ ☢ ]
+        let minefield = Minefield::from_fn(3, 3, |x, y| (x, y) != (1, 1));
+        assert_eq!(minefield.count_distribution(), [0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(Minefield::new(2, 2).count_distribution(), [4, 0, 0, 0, 0, 0, 0, 0, 0]);
+     }
+
+     #[test]
+     fn layout_stats() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+
+        // Both openings reveal all of the numbered spots
+        assert_eq!(minefield.bbbv(), 2);
+        assert_eq!(
+            minefield.layout_stats(),
+            LayoutStats { openings: 2, largest_opening: 6, isolated_regions: 0, density: 2.0 / 12.0 }
+        );
+
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ 3 ☢ 2 ]
+        // 1 [ ☢ ☢ 2 ]
+        // 2 [ 2 2 1 ]
+        let minefield = Minefield::from_fn(3, 3, |x, y| [(1, 0), (0, 1), (1, 1)].contains(&(x, y)));
+
+        // No openings: every empty spot takes its own step, and the spot in the corner is cut off from the others
+        assert_eq!(minefield.bbbv(), 6);
+        assert_eq!(
+            minefield.layout_stats(),
+            LayoutStats { openings: 0, largest_opening: 0, isolated_regions: 2, density: 3.0 / 9.0 }
+        );
+
+        // Without mines, the whole field is a single opening
+        let minefield = Minefield::new(4, 4);
+        assert_eq!(minefield.bbbv(), 1);
+        assert_eq!(
+            minefield.layout_stats(),
+            LayoutStats { openings: 1, largest_opening: 16, isolated_regions: 0, density: 0.0 }
+        );
+     }
+
+     #[test]
+     fn reserved_safe() {
+        // Reserve the top-left 3x3 corner
+        let corner: Vec<(Coord, Coord)> = (0..3).flat_map(|y| (0..3).map(move |x| (x, y))).collect();
+
+        for seed in 0..10 {
+            let minefield = Minefield::new(6, 6).with_reserved_safe(corner.iter().copied()).with_seeded_mines(20, seed);
+            assert_eq!(minefield.mines(), 20);
+
+            for &(x, y) in &corner {
+                assert!(!minefield.spot(x, y).unwrap().is_mine());
+            }
+        }
+
+        // Mines are clamped to the spots which aren't reserved
+        let minefield = Minefield::new(6, 6).with_reserved_safe(corner.iter().copied()).with_mines(100);
+        assert_eq!(minefield.mines(), 27);
+
+        // Symmetric placement leaves out the mirror images of reserved spots too
+        let minefield = Minefield::new(6, 6)
+            .with_reserved_safe(corner.iter().copied())
+            .with_symmetric_mines(100, Symmetry::Rotational, 3);
+        assert_eq!(minefield.mines(), 18);
+
+        for &(x, y) in &corner {
+            assert!(!minefield.spot(x, y).unwrap().is_mine());
+            assert!(!minefield.spot(5 - x, 5 - y).unwrap().is_mine());
+        }
+
+        // The first mine is never moved onto a reserved spot
+        let mut minefield = Minefield::from_fn(2, 2, |x, y| (x, y) == (1, 1))
+            .with_reserved_safe([(0, 0), (1, 0)])
+            .with_move_first_mine(true);
+        assert_eq!(minefield.step(1, 1), StepResult::Phew);
+        assert!(minefield.spot(0, 1).unwrap().is_mine());
+     }
+
+     #[test]
+     fn flood_through_flags() {
+        // Create minefield with mines
+        //     0 1 2 3 4
+        // 0 [           ]
+        // 1 [       1 1 ]
+        // 2 [       1 ☢ ]
+        let build = || Minefield::from_fn(5, 3, |x, y| (x, y) == (4, 2));
+
+        // Flags stop the flood by default
+        //     0 1 2 3 4
+        // 0 [     ⚐ • • ]
+        // 1 [     ⚐ • • ]
+        // 2 [     ⚐ • • ]
+        let mut minefield = build();
+        for y in 0..3 {
+            minefield.toggle_flag(2, y);
+        }
+        minefield.step(0, 0);
+        assert_eq!(minefield.spot(3, 0).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        assert_eq!(minefield.flags_placed(), 3);
+
+        // Flooding through flags clears them, and reveals the rest of the field
+        //     0 1 2 3 4
+        // 0 [           ]
+        // 1 [       1 1 ]
+        // 2 [       1 • ]
+        let mut minefield = build().with_flood_through_flags(true);
+        for y in 0..3 {
+            minefield.toggle_flag(2, y);
+        }
+        let result = minefield.apply(Action::Step { x: 0, y: 0 });
+        assert_eq!(result.changed.len(), 14);
+        assert_eq!(minefield.flags_placed(), 0);
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // Flags on spots with neighboring mines are kept
+        let mut minefield = build().with_flood_through_flags(true);
+        minefield.toggle_flag(3, 1);
+        minefield.step(0, 0);
+        assert_eq!(minefield.spot(3, 1).unwrap().state, SpotState::FlaggedEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.flags_placed(), 1);
+     }
+
+     #[test]
+     fn step_many() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+
+        // Invalid steps don't stop the batch
+        let batch = minefield.step_many(&[(1, 0), (5, 5), (1, 0), (1, 3)]);
+        assert_eq!(batch.results, vec![StepResult::Phew, StepResult::Invalid, StepResult::Invalid, StepResult::Phew]);
+        assert_eq!(batch.revealed, vec![(1, 0), (1, 3)]);
+        assert_eq!(batch.boom, None);
+
+        // The first step on a mine stops the batch
+        let batch = minefield.step_many(&[(0, 0), (0, 3), (2, 3)]);
+        assert_eq!(batch.results, vec![StepResult::Phew, StepResult::Boom]);
+        assert_eq!(batch.revealed.len(), 6);
+        assert!(batch.revealed.contains(&(0, 3)));
+        assert_eq!(batch.boom, Some((1, (0, 3))));
+        assert!(minefield.spot(2, 3).unwrap().is_hidden());
+     }
+
+     #[test]
+     fn binary_format() {
+        // Shaped minefield in the middle of a game
+        let mut minefield = Minefield::with_shape(8, 6, |x, y| (x, y) != (0, 0)).with_seeded_mines(10, 3);
+        let (x, y) = minefield.spots().find(|(_coords, spot)| !spot.is_mine()).unwrap().0;
+        minefield.step(x, y);
+        let (x, y) = minefield.spots().find(|(_coords, spot)| spot.is_hidden()).unwrap().0;
+        minefield.toggle_flag(x, y);
+
+        let bytes = minefield.to_bytes();
+        assert_eq!(bytes.len(), 17 + 8 * 6);
+
+        let decoded = Minefield::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, minefield);
+        assert_eq!(decoded.mines(), minefield.mines());
+        assert_eq!(decoded.flags_placed(), 1);
+        assert_eq!(decoded.state_hash(), minefield.state_hash());
+
+        // Truncated data
+        assert_eq!(Minefield::from_bytes(&bytes[..10]), Err(DecodeError::Length { expected: 17, actual: 10 }));
+        assert_eq!(
+            Minefield::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Length { expected: bytes.len(), actual: bytes.len() - 1 })
+        );
+
+        // Garbage
+        assert_eq!(Minefield::from_bytes(b"not a minefield at all"), Err(DecodeError::BadMagic));
+
+        let mut garbage = bytes.clone();
+        garbage[4] = 9;
+        assert_eq!(Minefield::from_bytes(&garbage), Err(DecodeError::UnsupportedVersion { version: 9 }));
+
+        let mut garbage = bytes.clone();
+        garbage[17 + 1] = 0xff;
+        assert_eq!(Minefield::from_bytes(&garbage), Err(DecodeError::InvalidSpot { x: 1, y: 0 }));
+
+        // Out of range counts, for empty spots and for mines
+        for byte in [0x0f, 0x09, 0x4c, 0x11, 0x5f] {
+            let mut garbage = bytes.clone();
+            garbage[17 + 1] = byte;
+            assert_eq!(Minefield::from_bytes(&garbage), Err(DecodeError::InvalidSpot { x: 1, y: 0 }));
+        }
+
+        let mut garbage = bytes.clone();
+        garbage[13] += 1;
+        assert!(matches!(
+            Minefield::from_bytes(&garbage),
+            Err(DecodeError::Inconsistent { error: ValidationError::MineCount { stored: 11, actual: 10 } })
+        ));
+     }
+
+     #[test]
+     fn progress() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.progress(), 0.0);
+        assert_eq!(minefield.safe_spots_remaining(), 10);
+
+        minefield.step(0, 0);
+        assert_eq!(minefield.progress(), 0.6);
+        assert_eq!(minefield.safe_spots_remaining(), 4);
+
+        // Flags make no progress
+        minefield.toggle_flag(2, 2);
+        minefield.toggle_flag(2, 0);
+        assert_eq!(minefield.progress(), 0.6);
+
+        minefield.toggle_flag(2, 2);
+        minefield.step(2, 2);
+        assert_eq!(minefield.progress(), 1.0);
+        assert_eq!(minefield.safe_spots_remaining(), 0);
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // Minefields without empty spots are done, and those without mines start at zero
+        assert_eq!(Minefield::new(2, 2).with_mines(4).progress(), 1.0);
+        assert_eq!(Minefield::new(2, 2).progress(), 0.0);
+
+        // Holes don't count
+        let mut minefield = Minefield::with_shape(2, 2, |x, y| (x, y) != (1, 1));
+        assert_eq!(minefield.safe_spots_remaining(), 3);
+        minefield.step(0, 0);
+        assert_eq!(minefield.progress(), 1.0);
+     }
+
+     #[test]
+     fn hidden_remaining() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (10, 12));
+
+        // Flags change neither
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(2, 3);
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (10, 12));
+        minefield.toggle_flag(2, 3);
+
+        // A flood reveals 6 spots at once
+        minefield.step(0, 0);
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (4, 6));
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+
+        // The game is won exactly when the last empty spots are revealed, here by a flood
+        minefield.step(2, 1);
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (3, 5));
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+        minefield.step(2, 2);
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (0, 2));
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // Revealed mines are no longer hidden, and an explosion loses whatever is left
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(2, 0);
+        assert_eq!((minefield.safe_spots_remaining(), minefield.hidden_remaining()), (10, 11));
+        assert_eq!(minefield.game_state(), GameState::Lost);
+     }
+
+     #[test]
+     fn mine_layout() {
+        let mut minefield = Minefield::new(7, 5).with_seeded_mines(9, 4);
+        let layout = minefield.mine_layout();
+        assert_eq!(layout.len(), 35);
+        assert_eq!(layout.iter().filter(|mine| **mine).count(), 9);
+
+        // Same positions, same counts
+        let from_layout = Minefield::from_mine_layout(7, 5, &layout);
+        assert_eq!(from_layout, minefield);
+        assert_eq!(from_layout.mines(), 9);
+        assert_eq!(from_layout.validate(), Ok(()));
+
+        // The layout ignores the state of the spots
+        let (x, y) = minefield.spots().find(|(_coords, spot)| spot.is_mine()).unwrap().0;
+        minefield.toggle_flag(x, y);
+        minefield.step(x, y);
+        assert_eq!(minefield.mine_layout(), layout);
+
+        // Short layouts are padded with empty spots
+        let minefield = Minefield::from_mine_layout(2, 2, &[false, true]);
+        assert_eq!(minefield.mine_layout(), vec![false, true, false, false]);
+        assert_eq!(minefield.spot(0, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+     }
+
+     #[test]
+     fn daily() {
+        // Golden layout, which must never change
+        let minefield = Minefield::daily(6, 4, 5, "2024-06-01");
+        let mines: Vec<(Coord, Coord)> = minefield
+            .spots()
+            .filter(|(_coords, spot)| spot.is_mine())
+            .map(|(coords, _spot)| coords)
+            .collect();
+        assert_eq!(mines, vec![(2, 0), (4, 1), (2, 2), (2, 3), (4, 3)]);
+
+        // Same key, same minefield, and different keys give different minefields
+        assert_eq!(Minefield::daily(16, 16, 40, "2024-06-01"), Minefield::daily(16, 16, 40, "2024-06-01"));
+        assert_ne!(Minefield::daily(16, 16, 40, "2024-06-01"), Minefield::daily(16, 16, 40, "2024-06-02"));
+        assert_eq!(Minefield::daily(4, 4, 100, "2024-06-01").mines(), 16);
+     }
+
+     #[test]
+     fn cell_data() {
+        let mut minefield = Minefield::new(3, 2).with_seeded_mines(2, 1).with_cell_data::<Option<&str>>();
+        assert_eq!(minefield.cell_data(1, 1), Some(&None));
+        assert_eq!(minefield.cell_data(3, 0), None);
+
+        *minefield.cell_data_mut(1, 1).unwrap() = Some("arrow");
+        assert_eq!(minefield.cell_data(1, 1), Some(&Some("arrow")));
+        assert_eq!(minefield.cell_data(0, 1), Some(&None));
+        assert!(minefield.cell_data_mut(0, 2).is_none());
+
+        // Playing keeps the data
+        let (x, y) = minefield.spots().find(|(_coords, spot)| !spot.is_mine()).unwrap().0;
+        minefield.step(x, y);
+        assert_eq!(minefield.cell_data(1, 1), Some(&Some("arrow")));
+        assert_eq!(minefield.player_view().cell_data(1, 1), Some(&Some("arrow")));
+
+        // Holes hold no data
+        let mut minefield = Minefield::with_shape(2, 2, |x, y| (x, y) != (0, 0)).with_cell_data::<u32>();
+        assert_eq!(minefield.cell_data(0, 0), None);
+        *minefield.cell_data_mut(1, 0).unwrap() += 5;
+        assert_eq!(minefield.cell_data(1, 0), Some(&5));
+     }
+
+     #[test]
+     fn equality() {
+        let minefield = Minefield::new(6, 5).with_seeded_mines(7, 2);
+        assert_eq!(minefield, Minefield::from_mine_layout(6, 5, &minefield.mine_layout()));
+        assert!(minefield.same_layout(&minefield));
+
+        // Playing changes the state, not the layout
+        let mut played = minefield.clone();
+        let (x, y) = played.spots().find(|(_coords, spot)| !spot.is_mine()).unwrap().0;
+        played.step(x, y);
+        played.toggle_flag(0, 0);
+        assert_ne!(played, minefield);
+        assert!(played.same_layout(&minefield));
+        assert!(played.same_layout(&minefield.clone().with_cell_data::<u8>()));
+
+        // Options are not compared
+        assert_eq!(minefield.clone().with_move_first_mine(true), minefield);
+
+        // Other layouts, or the same layout with other dimensions
+        let other = Minefield::new(6, 5).with_seeded_mines(7, 3);
+        assert_ne!(other, minefield);
+        assert!(!other.same_layout(&minefield));
+
+        let layout = Minefield::from_fn(2, 3, |x, y| (x, y) == (1, 0));
+        let transposed = Minefield::from_fn(3, 2, |x, y| (x, y) == (1, 0));
+        assert_eq!(layout.mine_layout(), transposed.mine_layout());
+        assert!(!layout.same_layout(&transposed));
+        assert_ne!(layout, transposed);
+     }
+
+     #[test]
+     fn has_started() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert!(!minefield.has_started());
+
+        // Flags, invalid steps and auto steps which do nothing don't start the game
+        minefield.toggle_flag(1, 1);
+        minefield.step(1, 1);
+        minefield.step(3, 0);
+        minefield.auto_step(0, 0);
+        assert!(!minefield.has_started());
+
+        minefield.step(1, 0);
+        assert!(minefield.has_started());
+
+        // Stepping on a mine starts the game too
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.step(2, 0);
+        assert!(minefield.has_started());
+     }
+
+     #[test]
+     fn move_count() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.move_count(), 0);
+
+        minefield.step(0, 0);
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(2, 0);
+        assert_eq!(minefield.move_count(), 4);
+        minefield.auto_step(1, 0);
+        assert_eq!(minefield.move_count(), 5);
+
+        // Invalid actions don't count
+        minefield.step(0, 0);
+        minefield.step(5, 5);
+        minefield.toggle_flag(1, 1);
+        minefield.toggle_flag(5, 5);
+        minefield.auto_step(1, 0);
+        minefield.auto_step(0, 2);
+        assert_eq!(minefield.move_count(), 5);
+
+        // Neither do flags refused by the flag limit
+        let mut minefield = Minefield::from_fn(2, 1, |x, _y| x == 0).limit_flags_to_mines(true);
+        minefield.toggle_flag(0, 0);
+        assert_eq!(minefield.toggle_flag(1, 0), FlagToggleResult::LimitReached);
+        assert_eq!(minefield.move_count(), 1);
+     }
+
+     #[test]
+     fn flag_modes() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+
+        // Flags only, by default
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::HiddenMine);
+
+        // Flags, then question marks
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.flags_placed(), 1);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Questioned);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::QuestionedMine);
+        assert_eq!(minefield.flags_placed(), 0);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::HiddenMine);
+
+        // Question marks never count as flags for auto steps
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(2, 0);
+        minefield.step(1, 0);
+        assert_eq!(minefield.auto_step(1, 0), StepResult::Invalid);
+
+        // Questioned mines are not resolved
+        minefield.step(0, 0);
+        minefield.step(2, 2);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Added);
+        assert!(!minefield.is_cleared());
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.flag_remaining_mines(), 1);
+        assert!(minefield.is_cleared());
+
+        // Questioned spots can still be stepped on, and are revealed by flood reveals
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        minefield.toggle_flag(0, 1);
+        minefield.toggle_flag(0, 1);
+        minefield.toggle_flag(1, 1);
+        minefield.toggle_flag(1, 1);
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::QuestionedEmpty { neighboring_mines: 1 });
+        minefield.step(0, 0);
+        assert_eq!(minefield.spot(0, 1).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 0 });
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Going back to flags only clears left over question marks
+        minefield.toggle_flag(2, 0);
+        minefield.toggle_flag(2, 0);
+        minefield.set_flag_mode(FlagMode::Flags);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::HiddenMine);
+     }
+
+     #[test]
+     fn spots_in_rect() {
+        let minefield = Minefield::with_shape(5, 4, |x, y| (x, y) != (2, 2));
+        let coords = |x0, y0, x1, y1| -> Vec<(Coord, Coord)> {
+            minefield.spots_in_rect(x0, y0, x1, y1).map(|(coords, _spot)| coords).collect()
+        };
+
+        assert_eq!(coords(1, 1, 3, 2), vec![(1, 1), (2, 1), (3, 1), (1, 2), (3, 2)]);
+
+        // Corners in any order
+        assert_eq!(coords(3, 2, 1, 1), coords(1, 1, 3, 2));
+
+        // Clamped to the minefield
+        assert_eq!(coords(3, 2, 100, 100), vec![(3, 2), (4, 2), (3, 3), (4, 3)]);
+        assert_eq!(coords(0, 0, Coord::MAX, Coord::MAX).len(), 19);
+        assert_eq!(coords(10, 0, 20, 3), vec![]);
+        assert_eq!(coords(0, 3, 0, 3), vec![(0, 3)]);
+
+        // Viewports are given by their top-left corner and size
+        let viewport = |x0, y0, width, height| -> Vec<(Coord, Coord)> {
+            minefield.spots_in_viewport(x0, y0, width, height).map(|(coords, _spot)| coords).collect()
+        };
+
+        assert_eq!(viewport(1, 1, 3, 2), coords(1, 1, 3, 2));
+        assert_eq!(viewport(3, 2, 100, 100), coords(3, 2, 4, 3));
+        assert_eq!(viewport(Coord::MAX, 0, Coord::MAX, 4), vec![]);
+        assert_eq!(viewport(0, 0, 1, 1), vec![(0, 0)]);
+        assert_eq!(viewport(0, 0, 0, 4), vec![]);
+        assert_eq!(viewport(0, 0, 5, 0), vec![]);
+     }
+
+     #[test]
+     fn deduce() {
+        // A 1-1-1 pattern along the edge: no count is decisive on its own, but the outer counts see a subset of the
+        // spots the middle one sees
+        //
+        //  ? * ?        ? * ?
+        //  1 1 1        x * x   (x = safe)
+        //  0 0 0
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| (x, y) == (1, 0));
+        assert_eq!(minefield.deduce(), Deductions::default());
+
+        assert_eq!(minefield.step(0, 2), StepResult::Phew);
+        let expected = Deductions { safe: vec![(0, 0), (2, 0)], mines: vec![(1, 0)] };
+        assert_eq!(minefield.deduce(), expected);
+
+        // Deducing doesn't change the field, and wrong flags don't fool it
+        let before = minefield.clone();
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.deduce(), expected);
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Removed);
+        assert_eq!(minefield, before);
+
+        // Once the safe spots are revealed, only the mine is left to prove
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.step(2, 0), StepResult::Phew);
+        assert_eq!(minefield.deduce(), Deductions { safe: vec![], mines: vec![(1, 0)] });
+     }
+
+     #[test]
+     fn hint_explained() {
+        // A 1-1-1 pattern along the edge, where the outer counts see a subset of the spots the middle one sees
+        //
+        //  ? * ?
+        //  1 1 1
+        //  0 0 0
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| (x, y) == (1, 0));
+        assert_eq!(minefield.hint_explained(), None);
+        assert_eq!(minefield.step(0, 2), StepResult::Phew);
+
+        let count = |x, y| ConstraintRef { coords: (x, y), neighboring_mines: 1 };
+        let expected = ExplainedHint { coords: (0, 0), kind: HintKind::Safe, because: vec![count(1, 1), count(2, 1)] };
+        assert_eq!(minefield.hint_explained(), Some(expected));
+
+        // With wrong flags on the safe spots, only the mine is left to hint, and its proof relies on theirs
+        let before = minefield.clone();
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        let expected = ExplainedHint {
+            coords: (1, 0),
+            kind: HintKind::Mine,
+            because: vec![count(0, 1), count(1, 1), count(2, 1)],
+        };
+        assert_eq!(minefield.hint_explained(), Some(expected));
+
+        // Once a safe spot is revealed, its count alone proves the mine
+        let mut minefield = before;
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let expected = ExplainedHint { coords: (1, 0), kind: HintKind::Mine, because: vec![count(0, 0)] };
+        assert_eq!(minefield.hint_explained(), Some(expected));
+
+        // Nothing to hint once the game is over
+        assert_eq!(minefield.step(1, 0), StepResult::Boom);
+        assert_eq!(minefield.hint_explained(), None);
+     }
+
+     #[test]
+     fn state_histogram() {
+        //  . . *        . . *
+        //  . . .   ->   F . 1
+        //  . . .        ? 1 .
+        //  * . .        * 1 .
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.step(1, 3), StepResult::Phew);
+        assert_eq!(minefield.step(1, 2), StepResult::Phew);
+        assert_eq!(minefield.step(2, 1), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(0, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 2), FlagToggleResult::Questioned);
+
+        let histogram = minefield.state_histogram();
+        assert_eq!(
+            histogram,
+            StateHistogram {
+                hidden_empty: 5,
+                hidden_mine: 2,
+                flagged_empty: 1,
+                revealed_empty: 3,
+                questioned_empty: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(histogram.total(), 3 * 4);
+
+        // Losing shows the mines and the wrong flag
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        minefield.finalize_loss();
+        let histogram = minefield.state_histogram();
+        assert_eq!(histogram.exploded_mine, 1);
+        assert_eq!(histogram.revealed_mine, 1);
+        assert_eq!(histogram.wrong_flag, 1);
+        assert_eq!(histogram.total(), 3 * 4);
+     }
+
+     #[test]
+     #[cfg(feature = "adversarial")]
+     fn adversarial() {
+        // The only empty spot is wherever the first step lands
+        for _ in 0..10 {
+            let mut minefield = Minefield::new(5, 5).with_mines(24).with_adversarial(true);
+            assert_eq!(minefield.step(3, 1), StepResult::Phew);
+            assert_eq!(minefield.game_state(), GameState::Won);
+            assert_eq!(minefield.mines(), 24);
+        }
+
+        //  a * c d *        a b c d e
+        //  . . . . .   ->   1 1 1 1 1   (c is safe, the mines are either at a and d, or at b and e)
+        //  . . . . .        0 0 0 0 0
+        let build = || {
+            let mut minefield = Minefield::from_fn(5, 3, |x, y| [(1, 0), (4, 0)].contains(&(x, y)));
+            assert_eq!(minefield.step(0, 2), StepResult::Phew);
+            minefield.with_adversarial(true)
+        };
+
+        // Guessing while c is known to be safe is punished, although a holds no mine yet
+        for _ in 0..10 {
+            let mut minefield = build();
+            assert_eq!(minefield.step(0, 0), StepResult::Boom);
+            assert_eq!(minefield.exploded_at(), Some((0, 0)));
+            assert_eq!(minefield.mines(), 2);
+        }
+
+        // Once c is revealed, guessing can't be avoided, and the guess is safe
+        for _ in 0..10 {
+            let mut minefield = build();
+            assert_eq!(minefield.step(2, 0), StepResult::Phew);
+            assert_eq!(minefield.step(3, 0), StepResult::Phew);
+            assert_eq!(minefield.deduce(), Deductions { safe: vec![(0, 0)], mines: vec![(1, 0), (4, 0)] });
+            assert!(minefield.validate().is_ok());
+        }
+
+        // A seeded game plays out the same every time, and so does its replay
+        let play = |seed| {
+            let mut minefield = Minefield::new(8, 8).with_seeded_mines(10, 1).with_seeded_adversarial(seed);
+            let mut replay = Replay::record(&mut minefield);
+            for (x, y) in [(0, 0), (7, 7), (0, 7), (7, 0), (4, 4), (3, 5)] {
+                replay.apply(Action::Step { x, y });
+            }
+
+            let (spec, actions) = replay.into_parts();
+            assert_eq!(spec.adversarial_seed, Some(seed));
+            assert_eq!(ReplayPlayer::new(spec, actions).seek(6), &minefield);
+            minefield
+        };
+
+        for seed in 0..10 {
+            assert_eq!(play(seed), play(seed));
+        }
+     }
+
+     #[test]
+     fn smart_chord() {
+        //  . 1 *
+        //  . 1 1
+        //  1 1 .
+        //  * . .
+        let build = || {
+            let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+            assert_eq!(minefield.step(0, 0), StepResult::Phew);
+            minefield
+        };
+
+        // Revealing: the flag accounts for the mine, so the other hidden neighbor is safe
+        let mut minefield = build();
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.smart_chord(1, 1), StepResult::Phew);
+        assert!(minefield.spot(2, 1).unwrap().is_revealed());
+        assert!(minefield.spot(2, 3).unwrap().is_revealed());
+
+        // A shown mine accounts for itself just like a flag
+        let mut minefield = build();
+        assert_eq!(minefield.apply_diff(&[((2, 0), SpotState::RevealedMine)]), Ok(()));
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+        assert_eq!(minefield.smart_chord(1, 1), StepResult::Phew);
+        assert!(minefield.spot(2, 1).unwrap().is_revealed());
+        assert!(minefield.spot(2, 2).unwrap().is_revealed());
+
+        // Nothing happens once the game is lost
+        let mut minefield = build();
+        assert_eq!(minefield.step(2, 0), StepResult::Boom);
+        assert_eq!(minefield.smart_chord(1, 1), StepResult::Invalid);
+        assert!(minefield.spot(2, 1).unwrap().is_hidden());
+
+        // Flagging: the only hidden neighbors are the missing mines
+        let mut minefield = build();
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.step(2, 3), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 3), FlagToggleResult::Questioned);
+        assert_eq!(minefield.smart_chord(2, 1), StepResult::Phew);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.smart_chord(1, 3), StepResult::Phew);
+        assert_eq!(minefield.spot(0, 3).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.flags_placed(), 2);
+
+        // Neither applies when the count doesn't settle the hidden neighbors, or the spot isn't revealed
+        let mut minefield = build();
+        assert_eq!(minefield.smart_chord(0, 2), StepResult::Invalid);
+        assert_eq!(minefield.smart_chord(2, 2), StepResult::Invalid);
+        assert_eq!(minefield.move_count(), 1);
+     }
+
+     #[test]
+     fn mines_spread() {
+        let spread_out = |minefield: &Minefield| {
+            minefield.spots().filter(|(_coords, spot)| spot.is_mine()).all(|((x, y), _spot)| {
+                minefield.neighbors(x, y).all(|(_n_coords, n_spot)| !n_spot.is_mine())
+            })
+        };
+
+        let minefield = Minefield::new(30, 16).with_mines_spread(60).unwrap();
+        assert_eq!(minefield.mines(), 60);
+        assert!(spread_out(&minefield));
+
+        // One mine per 2x2 block at most
+        for _ in 0..10 {
+            let minefield = Minefield::new(5, 5).with_mines_spread(9).unwrap();
+            assert_eq!(minefield.mines(), 9);
+            assert!(spread_out(&minefield));
+        }
+        assert_eq!(
+            Minefield::new(5, 5).with_mines_spread(10).unwrap_err(),
+            PlacementError::TooManyMines { mines: 10, max: 9 }
+        );
+
+        // Only the center is left for mines
+        let reserved = [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let build = || Minefield::new(3, 3).with_reserved_safe(reserved);
+        assert!(build().with_mines_spread(1).unwrap().spot(1, 1).unwrap().is_mine());
+        assert_eq!(build().with_mines_spread(2).unwrap_err(), PlacementError::Unsatisfiable { mines: 2 });
+     }
+
+     #[test]
+     fn builder() {
+        let minefield = Minefield::builder().width(9).height(9).mines(10).seed(42).build().unwrap();
+        assert_eq!(minefield, Minefield::new(9, 9).with_seeded_mines(10, 42));
+
+        let mut minefield = Minefield::builder()
+            .width(10)
+            .height(5)
+            .density(0.2)
+            .reserved_safe([(0, 0), (1, 0)])
+            .question_marks(true)
+            .build()
+            .unwrap();
+        assert_eq!(minefield.mines(), 10);
+        assert!(!minefield.spot(0, 0).unwrap().is_mine());
+        assert!(!minefield.spot(1, 0).unwrap().is_mine());
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Questioned);
+
+        assert_eq!(
+            Minefield::builder().width(9).height(9).mines(10).density(0.1).build().unwrap_err(),
+            BuildError::MinesAndDensity
+        );
+        assert_eq!(Minefield::builder().width(9).mines(10).build().unwrap_err(), BuildError::MissingSize);
+        assert_eq!(
+            Minefield::builder().width(2).height(2).mines(4).reserved_safe([(1, 1)]).build().unwrap_err(),
+            BuildError::TooManyMines { mines: 4, max: 3 }
+        );
+
+        // On a 3x3 torus, every spot neighbors every other spot
+        let minefield = Minefield::builder().width(3).height(3).mines(1).wrapping(true).build().unwrap();
+        assert!(minefield.spots().all(|(_coords, spot)| spot.is_mine() || spot.neighboring_mines() == Some(1)));
+     }
+
+     #[test]
+     fn wrapping() {
+        //     0 1 2 3
+        // 0 [ ☢ 1   1 ]
+        // 1 [ 1 1   1 ]
+        // 2 [ 1 1   1 ]
+        //
+        // once the edges wrap around
+        let minefield = Minefield::from_fn(4, 3, |x, y| (x, y) == (0, 0));
+        assert_eq!(minefield.spot(3, 2).unwrap().neighboring_mines(), Some(0));
+
+        let minefield = minefield.with_wrapping(true);
+        assert_eq!(minefield.spot(3, 0).unwrap().neighboring_mines(), Some(1));
+        assert_eq!(minefield.spot(3, 2).unwrap().neighboring_mines(), Some(1));
+        assert_eq!(minefield.spot(0, 2).unwrap().neighboring_mines(), Some(1));
+        assert_eq!(minefield.spot(2, 1).unwrap().neighboring_mines(), Some(0));
+        assert_eq!(minefield.neighbors(0, 0).count(), 8);
+        assert!(minefield.validate().is_ok());
+
+        // Flooding wraps around too, from the column on the right edge into the column on the left edge
+        let mut minefield = Minefield::from_fn(4, 3, |x, y| (x, y) == (1, 1)).with_wrapping(true);
+        assert_eq!(minefield.step(3, 1), StepResult::Phew);
+        assert!(minefield.spot(0, 1).unwrap().is_revealed());
+        assert_eq!(minefield.safe_spots_remaining(), 2);
+
+        // Mines placed afterwards wrap around
+        let mut minefield = Minefield::new(4, 3).with_wrapping(true);
+        minefield.place_mine(3, 2).unwrap();
+        assert_eq!(minefield.spot(0, 0).unwrap().neighboring_mines(), Some(1));
+
+        // Narrow minefields don't count the same neighbor twice
+        let minefield = Minefield::from_fn(2, 1, |x, _y| x == 0).with_wrapping(true);
+        assert_eq!(minefield.spot(1, 0).unwrap().neighboring_mines(), Some(1));
+        assert_eq!(minefield.neighbors(1, 0).count(), 1);
+     }
+
+     #[test]
+     fn mines_excluding_rect() {
+        for _ in 0..10 {
+            let mut minefield = Minefield::new(8, 8).with_mines_excluding_rect(48, (0, 0, 3, 3)).unwrap();
+            assert_eq!(minefield.mines(), 48);
+            assert!(minefield.spots_in_rect(0, 0, 3, 3).all(|(_coords, spot)| !spot.is_mine()));
+
+            // The rectangle isn't reserved afterwards
+            assert!(minefield.reserved.is_empty());
+
+            let batch = minefield.reveal_rect((0, 0, 3, 3));
+            assert_eq!(batch.boom, None);
+            assert!(minefield.spots_in_rect(0, 0, 3, 3).all(|(_coords, spot)| spot.is_revealed()));
+        }
+
+        assert_eq!(
+            Minefield::new(8, 8).with_mines_excluding_rect(49, (3, 3, 0, 0)).unwrap_err(),
+            PlacementError::TooManyMines { mines: 49, max: 48 }
+        );
+        assert_eq!(
+            Minefield::new(8, 8).with_mines_excluding_rect(10, (4, 4, 8, 7)).unwrap_err(),
+            PlacementError::OutOfBounds { x: 8, y: 7 }
+        );
+     }
+
+     #[test]
+     fn regenerate() {
+        let mut minefield = Minefield::new(16, 16).with_seeded_mines(40, 7);
+        minefield.enable_history();
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        minefield.step(8, 8);
+
+        minefield.regenerate(40, 7);
+        assert_eq!(minefield, Minefield::new(16, 16).with_seeded_mines(40, 7));
+        assert!(minefield.validate().is_ok());
+        assert!(!minefield.has_started());
+        assert_eq!(minefield.flags_placed(), 0);
+        assert_eq!(minefield.move_count(), 0);
+        assert!(minefield.history().is_empty());
+
+        let layout = minefield.mine_layout();
+        minefield.regenerate(40, 8);
+        assert_eq!(minefield.mines(), 40);
+        assert_ne!(minefield.mine_layout(), layout);
+
+        minefield.regenerate(10, 7);
+        assert_eq!(minefield.mines(), 10);
+     }
+
+     #[test]
+     fn generate_with_bbbv() {
+        let generated = Minefield::generate_with_bbbv(16, 16, 40, 50..=70, 5, 100).unwrap();
+        let bbbv = generated.minefield.bbbv() as u32;
+        assert!((50..=70).contains(&bbbv));
+        assert!(generated.attempts >= 1);
+        assert_eq!(generated.minefield, Minefield::new(16, 16).with_seeded_mines(40, generated.seed));
+
+        // Deterministic
+        let again = Minefield::generate_with_bbbv(16, 16, 40, 50..=70, 5, 100).unwrap();
+        assert_eq!((again.attempts, again.seed), (generated.attempts, generated.seed));
+
+        // Any 3BV matches the very first attempt
+        assert_eq!(Minefield::generate_with_bbbv(16, 16, 40, 0..=u32::MAX, 5, 1).unwrap().attempts, 1);
+
+        // No board of 4 spots has a 3BV of 5
+        assert_eq!(
+            Minefield::generate_with_bbbv(2, 2, 1, 5..=10, 5, 20).unwrap_err(),
+            GenerationError::NoMatch { attempts: 20 }
+        );
+     }
+
+     #[test]
+     fn render() {
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.render(), " 1F\n 1.\n11.\n...\n");
+
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        minefield.finalize_loss();
+        assert_eq!(minefield.render(), " 1F\n 1.\n11.\nX..\n");
+
+        let holed = Minefield::with_shape(2, 1, |x, _y| x == 0);
+        assert_eq!(holed.render(), ".#\n");
+     }
+
+     #[test]
+     fn render_charset() {
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.render_charset(&RenderCharset::default()), minefield.render());
+        assert_eq!(minefield.render_charset(&RenderCharset::UNICODE), " 1⚐\n 1•\n11•\n•••\n");
+
+        // Flags as emoji, and counts as letters
+        let charset = RenderCharset {
+            flag: '🚩',
+            digits: |count| char::from(b'a' + count - 1),
+            ..RenderCharset::ASCII
+        };
+        assert_eq!(minefield.step(0, 3), StepResult::Boom);
+        assert_eq!(minefield.render_charset(&charset), " a🚩\n a.\naa.\nX..\n");
+        assert_eq!(minefield.spot(1, 0).unwrap().glyph_in(&charset), 'a');
+     }
+
+     #[cfg(feature = "ansi")]
+     #[test]
+     fn render_ansi() {
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+
+        let rendered = minefield.render_ansi();
+        assert!(rendered.starts_with(" \x1b[94m1\x1b[0m\x1b[91mF\x1b[0m\n"));
+        assert!(rendered.ends_with("\n...\n"));
+     }
+
+     #[cfg(feature = "oracle")]
+     #[test]
+     fn oracle() {
+        //     0 1 2
+        // 0 [ ☢ 2 # ]
+        // 1 [ 2 ☢ 1 ]
+        let mut minefield = Minefield::with_shape(3, 2, |x, y| (x, y) != (2, 0));
+        assert_eq!(minefield.place_mine(0, 0), Ok(()));
+        assert_eq!(minefield.place_mine(1, 1), Ok(()));
+        assert_eq!(minefield.step(2, 1), StepResult::Phew);
+
+        let oracle = minefield.oracle();
+        assert!(oracle.is_mine(0, 0));
+        assert!(oracle.is_mine(1, 1));
+        assert!(!oracle.is_mine(2, 1));
+        assert!(!oracle.is_mine(2, 0));
+        assert!(!oracle.is_mine(3, 0));
+        assert_eq!(oracle.mine_mask(), vec![true, false, false, false, true, false]);
+        assert_eq!(oracle.true_counts(), vec![1, 2, 0, 2, 1, 1]);
+     }
+
+     #[test]
+     fn guess_difficulty() {
+        // Cleared by deduction from the opening
+        //
+        //  . 1 *
+        //  . 1 1
+        //  1 1 .
+        //  * 1 .
+        let minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.guess_difficulty(), 0);
+
+        // A 50/50 in the corner
+        //
+        //  * 1 . .
+        //  1 1 . .
+        let minefield = Minefield::from_fn(4, 2, |x, y| (x, y) == (0, 0));
+        assert_eq!(minefield.guess_difficulty(), 1);
+
+        // No opening: the first step is a guess, and so is the second one on the wider board
+        //
+        //  * 2 *        * 1 1 *
+        let minefield = Minefield::from_fn(3, 1, |x, _y| x != 1);
+        assert_eq!(minefield.guess_difficulty(), 1);
+        let minefield = Minefield::from_fn(4, 1, |x, _y| x == 0 || x == 3);
+        assert_eq!(minefield.guess_difficulty(), 2);
+
+        // The simulated game doesn't touch the minefield
+        assert!(minefield.spots().all(|(_coords, spot)| spot.is_hidden()));
+     }
+
+     #[test]
+     fn forced_guesses() {
+        //  . 1 *
+        //  . 1 1
+        //  1 1 .
+        //  * 1 .
+        let minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.forced_guesses((0, 0)), 0);
+        assert_eq!(minefield.forced_guesses((2, 3)), 0);
+
+        // Starting on a lone 1 in the middle leaves nothing provably safe
+        assert_eq!(minefield.forced_guesses((1, 1)), 1);
+
+        // Starting on a mine, or outside the minefield, makes the first step a guess
+        assert_eq!(minefield.forced_guesses((2, 0)), 1);
+        assert_eq!(minefield.forced_guesses((5, 5)), 1);
+
+        // The same start always takes as many guesses, and the simulated game doesn't touch the minefield
+        assert_eq!(minefield.forced_guesses((1, 1)), minefield.forced_guesses((1, 1)));
+        assert!(minefield.spots().all(|(_coords, spot)| spot.is_hidden()));
+     }
+
+     #[test]
+     fn infinite_minefield() {
+        let mut world = InfiniteMinefield::new(7, 0.1);
+        assert_eq!(world.loaded_chunks().count(), 0);
+
+        // Same contents whatever the order of generation
+        let mut other = InfiniteMinefield::new(7, 0.1);
+        other.toggle_flag(-1000, 1000);
+        world.toggle_flag(0, 0);
+        world.toggle_flag(0, 0);
+        other.toggle_flag(0, 0);
+        other.toggle_flag(0, 0);
+        let contents = |world: &InfiniteMinefield| world.chunk_spots(0, 0).collect::<Vec<_>>();
+        assert_eq!(contents(&world).len(), 64 * 64);
+        assert_eq!(contents(&world), contents(&other));
+        let mut reseeded = InfiniteMinefield::new(8, 0.1);
+        reseeded.toggle_flag(0, 0);
+        reseeded.toggle_flag(0, 0);
+        assert_ne!(contents(&world), contents(&reseeded));
+        assert!(world.chunk_spots(5, 5).next().is_none());
+
+        // Negative coordinates map into their own chunks
+        assert_eq!(InfiniteMinefield::chunk_coords(-1, -64), (-1, -1));
+        assert_eq!(InfiniteMinefield::chunk_coords(-65, 63), (-2, 0));
+        assert_eq!(InfiniteMinefield::chunk_index(-1, -64), 63);
+
+        // Flood reveal from an empty spot on the right border of a chunk crosses into the next chunk
+        let y = (0..64)
+            .find(|&y| world.spot(63, y).state == (SpotState::HiddenEmpty { neighboring_mines: 0 }))
+            .unwrap();
+        assert_eq!(world.step(63, y), StepResult::Phew);
+        assert!(world.spot(64, y).is_revealed());
+        assert!(world.loaded_chunks().any(|chunk| chunk == (1, 0)));
+        assert_eq!(world.step(63, y), StepResult::Invalid);
+
+        // Flags and auto steps work as on finite minefields
+        let mine = (-10..10).find(|&x| world.spot(x, -100).is_mine()).unwrap();
+        assert_eq!(world.toggle_flag(mine, -100), FlagToggleResult::Added);
+        assert!(world.spot(mine, -100).is_flagged());
+        assert_eq!(world.step(mine, -100), StepResult::Invalid);
+        assert_eq!(world.toggle_flag(mine, -100), FlagToggleResult::Removed);
+        assert_eq!(world.step(mine, -100), StepResult::Boom);
+        assert_eq!(world.auto_step(mine, -100), StepResult::Invalid);
+
+        // A world without mines is flooded up to the limit
+        let mut world = InfiniteMinefield::new(7, 0.0);
+        assert_eq!(world.step(0, 0), StepResult::Phew);
+        let revealed = world
+            .loaded_chunks()
+            .flat_map(|(cx, cy)| world.chunk_spots(cx, cy))
+            .filter(|(_coords, spot)| spot.is_revealed())
+            .count();
+        assert_eq!(revealed, InfiniteMinefield::FLOOD_LIMIT);
+        assert_eq!(world.auto_step(0, 0), StepResult::Phew);
+     }
+
+     #[test]
+     fn reveal_all() {
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(1, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 3), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 3), FlagToggleResult::Questioned);
+
+        minefield.reveal_all();
+        assert!(minefield.spots().all(|(_coords, spot)| spot.is_revealed()));
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::RevealedMine);
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.flags_placed(), 0);
+        assert_eq!(minefield.exploded_at(), None);
+        assert_ne!(minefield.game_state(), GameState::Lost);
+        assert_eq!(minefield.safe_spots_remaining(), 0);
+        assert_eq!(minefield.game_state(), GameState::InProgress);
+        assert!(minefield.validate().is_ok());
+     }
+
+     #[test]
+     fn listener() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum Event {
+            Reveal((Coord, Coord), u8),
+            Flag((Coord, Coord), bool),
+            Explode((Coord, Coord)),
+            Cleared,
+        }
+
+        struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<Event>>>);
+
+        impl FieldListener for Recorder {
+            fn on_reveal(&mut self, coords: (Coord, Coord), neighboring_mines: u8) {
+                self.0.lock().unwrap().push(Event::Reveal(coords, neighboring_mines));
+            }
+
+            fn on_flag(&mut self, coords: (Coord, Coord), added: bool) {
+                self.0.lock().unwrap().push(Event::Flag(coords, added));
+            }
+
+            fn on_explode(&mut self, coords: (Coord, Coord)) {
+                self.0.lock().unwrap().push(Event::Explode(coords));
+            }
+
+            fn on_cleared(&mut self) {
+                self.0.lock().unwrap().push(Event::Cleared);
+            }
+        }
+
+        //  . 1 *
+        //  . 1 1
+        //  1 1 .
+        //  * 1 .
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let take = || events.lock().unwrap().drain(..).collect::<Vec<_>>();
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.set_listener(Box::new(Recorder(events.clone())));
+
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let flood = take();
+        assert_eq!(flood.len(), 6);
+        assert_eq!(flood[0], Event::Reveal((0, 0), 0));
+        assert!(flood.contains(&Event::Reveal((1, 2), 1)));
+
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::Removed);
+        assert_eq!(take(), vec![Event::Flag((2, 0), true), Event::Flag((2, 1), true), Event::Flag((2, 1), false)]);
+
+        // Nothing changed, nothing to tell
+        assert_eq!(minefield.step(0, 0), StepResult::Invalid);
+        assert_eq!(take(), vec![]);
+
+        // Clones don't share the listener
+        let mut clone = minefield.clone();
+        assert_eq!(clone.step(2, 1), StepResult::Phew);
+        assert_eq!(take(), vec![]);
+
+        assert_eq!(minefield.auto_step(1, 1), StepResult::Phew);
+        let events_after_auto_step = take();
+        assert_eq!(events_after_auto_step.last(), Some(&Event::Cleared));
+        assert!(events_after_auto_step.contains(&Event::Reveal((2, 3), 0)));
+
+        assert!(minefield.take_listener().is_some());
+        assert!(minefield.take_listener().is_none());
+
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        minefield.set_listener(Box::new(Recorder(events.clone())));
+        assert_eq!(minefield.step(2, 0), StepResult::Boom);
+        assert_eq!(take(), vec![Event::Explode((2, 0))]);
+     }
+
+     #[test]
+     fn sparse_mines() {
+        let minefield = Minefield::new(10000, 10000).with_mines(50);
+        assert_eq!(minefield.mines(), 50);
+        assert_eq!(minefield.field.iter().filter(|packed| Spot::from(**packed).is_mine()).count(), 50);
+
+        // Holes and reserved spots are skipped, and mines already in place are replaced
+        let minefield = Minefield::with_shape(100, 100, |x, _y| x % 2 == 0)
+            .with_reserved_safe((0..100).map(|y| (0, y)))
+            .with_seeded_mines(20, 3)
+            .with_seeded_mines(20, 4);
+        assert_eq!(minefield.mines(), 20);
+        assert!(minefield.spots().all(|((x, _y), spot)| !spot.is_mine() || x > 0));
+        assert!(minefield.validate().is_ok());
+
+        // Revealed spots can't take mines, so after a flood only the spots left hidden are filled
+        let mut minefield = Minefield::new(10, 10);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let minefield = minefield.with_mines(1);
+        assert_eq!(minefield.mines(), 0);
+
+        let mut minefield = Minefield::from_fn(100, 100, |x, y| (x, y) == (99, 99));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let minefield = minefield.with_mines(5);
+        assert_eq!(minefield.mines(), 1);
+        assert!(minefield.spot(99, 99).unwrap().is_mine());
+        assert!(minefield.validate().is_ok());
+     }
+
+     #[test]
+     fn auto_step_cascade() {
+        //  . 1 * 1 .         . 1 F 1 .
+        //  . 1 1 2 1   ->    . 1 1 2 1
+        //  . . . 1 *         . . . 1 F
+        //  . . . 1 1         . . . 1 1
+        //
+        // After flagging both mines, chording the top 1 next to the first mine reveals the 2, which chords the rest
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(4, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.step(3, 0), StepResult::Phew);
+
+        let batch = minefield.auto_step_cascade(3, 0);
+        assert_eq!(batch.boom, None);
+        assert!(batch.results.len() > 1);
+        assert!(batch.results.iter().all(|&result| result == StepResult::Phew));
+        assert!(batch.revealed.contains(&(3, 1)));
+        assert!(batch.revealed.contains(&(0, 3)));
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // A wrong flag makes the cascade step on a mine
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(3, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.step(3, 0), StepResult::Phew);
+
+        let batch = minefield.auto_step_cascade(3, 0);
+        assert_eq!(batch.results.last(), Some(&StepResult::Boom));
+        assert_eq!(batch.boom, Some((batch.results.len() - 1, (4, 2))));
+
+        // Nothing to chord
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        let batch = minefield.auto_step_cascade(3, 0);
+        assert_eq!(batch.results, vec![StepResult::Invalid]);
+        assert!(batch.revealed.is_empty());
+     }
+
+     #[test]
+     fn deduce_around() {
+        //  . 1 * . .        . 1 F 1 .
+        //  . 1 1 2 .   ->   . 1 1 2 1
+        //  . . . 1 .        . . . 1 F
+        //  . . . 1 .        . . . 1 1
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        minefield.enable_history();
+
+        // Flag the mine next to the 1, which lets the 1 below reveal the spot next to it, and so on
+        assert_eq!(minefield.deduce_around(1, 0), 6);
+        assert_eq!(minefield.game_state(), GameState::Won);
+        assert_eq!(minefield.spot(2, 0).unwrap().state, SpotState::FlaggedMine);
+        assert_eq!(minefield.spot(4, 2).unwrap().state, SpotState::FlaggedMine);
+        assert!(minefield.history().iter().all(|action| matches!(action.action, Action::SmartChord { .. })));
+
+        // Nothing left to deduce
+        assert_eq!(minefield.deduce_around(1, 0), 0);
+
+        // Hidden spots can't start a deduction
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.deduce_around(0, 0), 0);
+     }
+
+     #[test]
+     fn solve_step() {
+        //  . 1 * . .        . 1 F 1 .
+        //  . 1 1 2 .   ->   . 1 1 2 1
+        //  . . . 1 .        . . . 1 F
+        //  . . . 1 .        . . . 1 1
+        let build = || {
+            let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+            assert_eq!(minefield.step(0, 0), StepResult::Phew);
+            minefield
+        };
+
+        let mut minefield = build();
+        assert_eq!(minefield.solve_step(), SolveStepReport { flagged: vec![(2, 0)], revealed: vec![] });
+        assert_eq!(minefield.solve_step(), SolveStepReport { flagged: vec![], revealed: vec![(3, 0)] });
+        assert_eq!(minefield.solve_step(), SolveStepReport { flagged: vec![], revealed: vec![(4, 0), (4, 1)] });
+        assert_eq!(minefield.solve_step(), SolveStepReport { flagged: vec![(4, 2)], revealed: vec![] });
+        assert_eq!(minefield.solve_step(), SolveStepReport { flagged: vec![], revealed: vec![(4, 3)] });
+        assert!(minefield.solve_step().is_empty());
+        assert_eq!(minefield.game_state(), GameState::Won);
+
+        // A wrong flag next to the 1 on the right makes its other hidden neighbors look safe
+        let mut minefield = build();
+        assert_eq!(minefield.toggle_flag(4, 1), FlagToggleResult::Added);
+        let report = minefield.solve_step();
+        assert_eq!(report.revealed, vec![(4, 2)]);
+        assert_eq!(minefield.game_state(), GameState::Lost);
+     }
+
+     #[test]
+     fn is_consistent() {
+        //  . 1 * . .
+        //  . 1 1 2 .
+        //  . . . 1 .
+        //  . . . 1 .
+        let build = || {
+            let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+            assert_eq!(minefield.step(0, 0), StepResult::Phew);
+            minefield
+        };
+
+        let mut minefield = build();
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::Consistent);
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::Consistent);
+
+        // Two flags around the top 1
+        assert_eq!(minefield.toggle_flag(3, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::Violated { x: 2, y: 1 });
+
+        // The top 1 needs the mine next to it, and the bottom 1 needs another one, but the flag in the corner takes
+        // the last but one mine
+        let mut minefield = build();
+        assert_eq!(minefield.toggle_flag(4, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::Contradiction);
+
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| (x, y) == (0, 0));
+        assert_eq!(minefield.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.toggle_flag(2, 2), FlagToggleResult::Added);
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::TooManyFlags { flags: 2, mines: 1 });
+
+        // A whole row of hidden spots next to revealed counts is too much to search
+        let mut minefield = Minefield::from_fn(60, 3, |x, y| y == 0 && x % 3 == 0);
+        assert_eq!(minefield.step(0, 2), StepResult::Phew);
+        assert_eq!(minefield.is_consistent(), ConsistencyReport::LocallyConsistent);
+     }
+
+     #[test]
+     fn checkpoint() {
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let checkpoint = minefield.checkpoint();
+
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.step(4, 2), StepResult::Boom);
+        assert_ne!(minefield, checkpoint);
+
+        assert_eq!(minefield.restore(checkpoint.clone()), Ok(()));
+        assert_eq!(minefield, checkpoint);
+        assert_eq!(minefield.flags_placed(), 0);
+        assert_eq!(minefield.exploded_at(), None);
+        assert_eq!(minefield.move_count(), checkpoint.move_count());
+
+        // A checkpoint of another minefield doesn't fit
+        assert_eq!(minefield.restore(Minefield::new(4, 5)), Err(DiffError::ShapeMismatch));
+        assert_eq!(minefield, checkpoint);
+     }
+
+     #[test]
+     fn remaining_mine_bounds() {
+        //  . 1 * . .
+        //  . 1 1 2 .
+        //  . . . 1 .
+        //  . . . 1 .
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+
+        // The top 1 needs a mine, and the right column another one
+        assert_eq!(minefield.remaining_mine_bounds(), (2, 2));
+
+        // Every unknown spot left is proved to be a mine, whatever the flags say
+        //  . . 1 * . 1 .
+        let mut minefield = Minefield::from_fn(7, 1, |x, _y| x == 3 || x == 6);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.step(4, 0), StepResult::Phew);
+        assert_eq!(minefield.step(5, 0), StepResult::Phew);
+        assert_eq!(minefield.toggle_flag(6, 0), FlagToggleResult::Added);
+        assert_eq!(minefield.remaining_mine_bounds(), (2, 2));
+        assert_eq!(minefield.spots().filter(|(_coords, spot)| !spot.is_revealed()).count(), 2);
+
+        // Without revealed counts only the number of mines bounds the unknown spots
+        let minefield = Minefield::new(4, 4).with_seeded_mines(5, 1);
+        assert_eq!(minefield.remaining_mine_bounds(), (0, 5));
+     }
+
+     #[test]
+     fn has_contradiction() {
+        //  . 1 * . .
+        //  . 1 1 2 .
+        //  . . . 1 .
+        //  . . . 1 .
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert!(!minefield.has_contradiction());
+
+        // Two flags around the top 1
+        assert_eq!(minefield.toggle_flag(2, 0), FlagToggleResult::Added);
+        assert!(!minefield.has_contradiction());
+        assert_eq!(minefield.toggle_flag(3, 0), FlagToggleResult::Added);
+        assert!(minefield.has_contradiction());
+
+        // The only hidden neighbor of the top 1 is shown as empty, leaving no room for its mine
+        let mut minefield = Minefield::from_fn(5, 4, |x, y| [(2, 0), (4, 2)].contains(&(x, y)));
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let empty = SpotState::RevealedEmpty { neighboring_mines: 1 };
+        assert_eq!(minefield.apply_diff(&[((2, 0), empty)]), Ok(()));
+        assert!(minefield.has_contradiction());
+     }
+
+     #[test]
+     fn stats() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.difficulty(Difficulty::Beginner), None);
+
+        let won = |bbbv, seconds| GameRecord { won: true, moves: 20, bbbv, elapsed: Duration::from_secs(seconds) };
+        stats.record(Difficulty::Beginner, won(30, 15));
+        stats.record(Difficulty::Beginner, won(40, 10));
+        let lost = GameRecord { won: false, moves: 3, bbbv: 50, elapsed: Duration::from_secs(1) };
+        stats.record(Difficulty::Beginner, lost);
+        stats.record(Difficulty::Expert, won(200, 100));
+
+        let beginner = stats.difficulty(Difficulty::Beginner).unwrap();
+        assert_eq!(beginner.games, 3);
+        assert_eq!(beginner.wins, 2);
+        assert!((beginner.win_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(beginner.total_moves, 43);
+        assert_eq!(beginner.total_bbbv, 120);
+        assert_eq!(beginner.total_time, Duration::from_secs(26));
+        assert_eq!(beginner.best_time, Some(Duration::from_secs(10)));
+
+        // The lost game doesn't count for the best 3BV/s
+        assert_eq!(beginner.best_bbbv_per_second, Some(4.0));
+        assert_eq!(stats.difficulty(Difficulty::Expert).unwrap().best_bbbv_per_second, Some(2.0));
+        assert_eq!(stats.difficulties().count(), 2);
+
+        // Records from finished games only
+        let mut minefield = Minefield::from_fn(3, 1, |x, _y| x == 2);
+        assert_eq!(GameRecord::from_minefield(&minefield, Duration::from_secs(5)), None);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        let record = GameRecord::from_minefield(&minefield, Duration::from_secs(5)).unwrap();
+        assert_eq!(record, GameRecord { won: true, moves: 1, bbbv: 1, elapsed: Duration::from_secs(5) });
+        assert_eq!(Difficulty::Expert.settings(), (30, 16, 99));
+     }
+
+     #[test]
+     fn largest_revealed_region() {
+        // Create a field split in two by a wall of mines
+        //     0 1 2 3 4 5 6
+        // 0 [   2 ☢ 2       ]
+        // 1 [   3 ☢ 3       ]
+        // 2 [   3 ☢ 3       ]
+        // 3 [   2 ☢ 2       ]
+        let mut minefield = Minefield::from_fn(7, 4, |x, _y| x == 2);
+        assert_eq!(minefield.largest_revealed_region(), 0);
+
+        // Reveal a single spot on the right
+        minefield.step(3, 0);
+        assert_eq!(minefield.largest_revealed_region(), 1);
+
+        // Flood reveal the left side
+        minefield.step(0, 0);
+        assert_eq!(minefield.largest_revealed_region(), 8);
+
+        // Flood reveal the right side, which also touches the spot revealed first
+        minefield.step(6, 3);
+        assert_eq!(minefield.largest_revealed_region(), 16);
+        let revealed = |index| matches!(minefield.get(index).state, SpotState::RevealedEmpty { .. });
+        assert_eq!(minefield.connected_regions(revealed).len(), 2);
+     }
+
+     #[test]
+     fn enumerate_consistent_mines() {
+        let arrangements = |minefield: &Minefield| {
+            let mut arrangements: Vec<Vec<(Coord, Coord)>> = minefield
+                .enumerate_consistent_mines()
+                .into_iter()
+                .map(|arrangement| {
+                    let mut arrangement: Vec<_> = arrangement.into_iter().collect();
+                    arrangement.sort();
+                    arrangement
+                })
+                .collect();
+            arrangements.sort();
+            arrangements
+        };
+
+        // The revealed 1 can't tell which side the mine is on
+        //     0 1 2
+        // 0 [ • 1 • ]
+        let mut minefield = Minefield::from_fn(3, 1, |x, _y| x == 0);
+        minefield.step(1, 0);
+        assert_eq!(arrangements(&minefield), vec![vec![(0, 0)], vec![(2, 0)]]);
+
+        // Flags don't change the possible arrangements
+        minefield.toggle_flag(2, 0);
+        assert_eq!(arrangements(&minefield), vec![vec![(0, 0)], vec![(2, 0)]]);
+
+        // Two mines, one of them on the other side of the revealed 1
+        //     0 1 2 3
+        // 0 [ • 1 • • ]
+        // 1 [ • 1 • • ]
+        let mut minefield = Minefield::from_fn(4, 2, |x, y| (x, y) == (0, 0) || (x, y) == (3, 1));
+        minefield.step(1, 0);
+        minefield.step(1, 1);
+        let found = arrangements(&minefield);
+        assert_eq!(found.len(), 2 * 4);
+        for arrangement in &found {
+            assert_eq!(arrangement.len(), 2);
+            let left = arrangement.iter().filter(|(x, _y)| *x == 0).count();
+            let middle = arrangement.iter().filter(|(x, _y)| *x == 2).count();
+            let right = arrangement.iter().filter(|(x, _y)| *x == 3).count();
+            assert_eq!(left + middle, 1);
+            assert_eq!(right, 1);
+        }
+
+        // Exploded mines are known
+        //     0 1 2
+        // 0 [ 💥 2 • ]
+        let mut minefield = Minefield::from_fn(3, 1, |x, _y| x != 1);
+        minefield.step(1, 0);
+        minefield.step(0, 0);
+        assert_eq!(arrangements(&minefield), vec![vec![(0, 0), (2, 0)]]);
+     }
+
+     #[test]
+     fn eq_and_hash() {
+        use std::collections::HashSet;
+
+        // Same seed, same minefield
+        let minefield = Minefield::new(10, 10).with_seeded_mines(20, 3);
+        let same = Minefield::new(10, 10).with_seeded_mines(20, 3);
+        assert_eq!(minefield, same);
+
+        let mut set = HashSet::new();
+        assert!(set.insert(minefield.clone()));
+        assert!(!set.insert(same));
+
+        // A single flag makes a difference
+        let mut flagged = minefield.clone();
+        assert_eq!(flagged.toggle_flag(0, 0), FlagToggleResult::Added);
+        assert_ne!(minefield, flagged);
+        assert!(set.insert(flagged.clone()));
+
+        // Removing the flag makes them equal again
+        flagged.toggle_flag(0, 0);
+        assert_eq!(minefield, flagged);
+        assert!(set.contains(&flagged));
+
+        // Dimensions matter, even with the same number of spots
+        assert_ne!(Minefield::new(2, 3), Minefield::new(3, 2));
+     }
+
+     #[test]
+     fn symmetric_mines() {
+        for axis in [Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Rotational] {
+            for seed in 0..10 {
+                let minefield = Minefield::new(9, 6).with_symmetric_mines(20, axis, seed);
+                assert_eq!(minefield.mines(), 20);
+                assert_eq!(minefield.validate(), Ok(()));
+
+                // Every mine has a mirror image
+                for ((x, y), spot) in minefield.spots() {
+                    if spot.state == SpotState::HiddenMine {
+                        let (mirror_x, mirror_y) = axis.mirror(x, y, 9, 6);
+                        assert_eq!(minefield.spot(mirror_x, mirror_y).unwrap().state, SpotState::HiddenMine);
+                    }
+                }
+
+                // Same seed, same mines
+                let same = Minefield::new(9, 6).with_symmetric_mines(20, axis, seed);
+                assert_eq!(minefield.state_hash(), same.state_hash());
+            }
+        }
+
+        // Odd number of mines is reached through the center spot
+        let minefield = Minefield::new(5, 5).with_symmetric_mines(3, Symmetry::Rotational, 1);
+        assert_eq!(minefield.mines(), 3);
+        assert_eq!(minefield.spot(2, 2).unwrap().state, SpotState::HiddenMine);
+
+        // Without spots which are their own mirror image, only an even number of mines can be placed
+        let minefield = Minefield::new(4, 4).with_symmetric_mines(5, Symmetry::Rotational, 1);
+        assert_eq!(minefield.mines(), 4);
+
+        // Filling the field with mines
+        let minefield = Minefield::new(5, 5).with_symmetric_mines(100, Symmetry::Vertical, 1);
+        assert_eq!(minefield.mines(), 25);
+
+        // Unseeded placement, where the layout maps onto itself under the transform
+        for symmetry in [Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Rotational] {
+            let minefield = Minefield::new(7, 7).with_mines_symmetric(11, symmetry);
+            assert_eq!(minefield.mines(), 11);
+            assert_eq!(minefield.validate(), Ok(()));
+
+            for ((x, y), spot) in minefield.spots() {
+                let (mirror_x, mirror_y) = symmetry.mirror(x, y, 7, 7);
+                assert_eq!(minefield.spot(mirror_x, mirror_y).unwrap().is_mine(), spot.is_mine());
+            }
+        }
+     }
+
+     #[test]
+     fn flood_from() {
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        let zero = SpotState::RevealedEmpty { neighboring_mines: 0 };
+        assert_eq!(minefield.apply_diff(&[((0, 0), zero)]), Ok(()));
+
+        assert_eq!(minefield.flood_from(0, 0), vec![(1, 0), (0, 1), (1, 1), (0, 2), (1, 2)]);
+        assert_eq!(minefield.flood_from(0, 0), vec![]);
+        assert!(minefield.spot(2, 2).unwrap().is_hidden());
+
+        // Only revealed spots with no neighboring mines flood
+        assert_eq!(minefield.flood_from(1, 0), vec![]);
+        assert_eq!(minefield.flood_from(2, 2), vec![]);
+        assert_eq!(minefield.flood_from(7, 7), vec![]);
+     }
+
+     #[test]
+     fn diff() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [   1 ☢ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ ☢ 1   ]
+        let minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
+        assert_eq!(minefield.diff(&minefield), Ok(vec![]));
+
+        // Play some moves on a copy
+        //     0 1 2
+        // 0 [ • 1 • ]
+        // 1 [ • 1 1 ]
+        // 2 [ • 1   ]
+        // 3 [ ⚐ 1   ]
+        let mut played = minefield.clone();
+        played.toggle_flag(0, 3);
+        played.step(1, 0);
+        played.step(2, 3);
+
+        let diff = minefield.diff(&played).unwrap();
+        assert_eq!(
+            diff,
+            vec![
+                ((1, 0), SpotState::RevealedEmpty { neighboring_mines: 1 }),
+                ((1, 1), SpotState::RevealedEmpty { neighboring_mines: 1 }),
+                ((2, 1), SpotState::RevealedEmpty { neighboring_mines: 1 }),
+                ((1, 2), SpotState::RevealedEmpty { neighboring_mines: 1 }),
+                ((2, 2), SpotState::RevealedEmpty { neighboring_mines: 0 }),
+                ((0, 3), SpotState::FlaggedMine),
+                ((1, 3), SpotState::RevealedEmpty { neighboring_mines: 1 }),
+                ((2, 3), SpotState::RevealedEmpty { neighboring_mines: 0 }),
+            ]
+        );
+
+        // Patching the original with the diff catches it up
+        let mut patched = minefield.clone();
+        assert_eq!(patched.apply_diff(&diff), Ok(()));
+        assert_eq!(patched.diff(&played), Ok(vec![]));
+
+        // Explosions patched in and out decide whether the game is lost
+        assert_eq!(patched.apply_diff(&[((2, 0), SpotState::ExplodedMine)]), Ok(()));
+        assert_eq!(patched.game_state(), GameState::Lost);
+        assert_eq!(patched.exploded_at(), Some((2, 0)));
+        assert_eq!(patched.apply_diff(&[((2, 0), SpotState::HiddenMine)]), Ok(()));
+        assert_eq!(patched.game_state(), GameState::InProgress);
+        assert_eq!(patched.exploded_at(), None);
+
+        // Minefields of different shapes can't be compared
+        assert_eq!(minefield.diff(&Minefield::new(4, 3)), Err(DiffError::ShapeMismatch));
+        assert_eq!(minefield.diff(&Minefield::with_shape(3, 4, |x, _y| x != 1)), Err(DiffError::ShapeMismatch));
+
+        // Diffs outside the minefield are rejected as a whole
+        let mut patched = minefield.clone();
+        assert_eq!(
+            patched.apply_diff(&[
+                ((0, 0), SpotState::FlaggedEmpty { neighboring_mines: 0 }),
+                ((3, 0), SpotState::HiddenMine),
+            ]),
+            Err(DiffError::OutOfBounds { x: 3, y: 0 })
+        );
+        assert_eq!(patched.diff(&minefield), Ok(vec![]));
+     }
+
+     #[test]
+     fn move_first_mine() {
+        // Create minefield with mines
+        //     0 1 2
+        // 0 [ ☢ 2 ☢ ]
+        // 1 [ 1 2 1 ]
+        // 2 [       ]
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| y == 0 && x != 1).with_move_first_mine(true);
+
+        // First step on a mine moves it to the first free spot
+        //     0 1 2
+        // 0 [ 1 ☢ ☢ ]
+        // 1 [ 1 2 2 ]
+        // 2 [       ]
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+        assert_eq!(minefield.spot(0, 0).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.spot(1, 0).unwrap().state, SpotState::HiddenMine);
+        assert_eq!(minefield.spot(1, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 2 });
+        assert_eq!(minefield.spot(0, 1).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        assert_eq!(minefield.mines(), 2);
+        assert_eq!(minefield.validate(), Ok(()));
+
+        // Later steps on mines explode
+        assert_eq!(minefield.step(1, 0), StepResult::Boom);
 
-    /// Stepped on a mine
-    Boom,
+        // Without the option, the first step can explode
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| y == 0 && x != 1);
+        assert_eq!(minefield.step(0, 0), StepResult::Boom);
 
-    /// Step not taken
-    Invalid
-}
+        // An invalid first step doesn't count as the first step
+        let mut minefield = Minefield::from_fn(3, 3, |x, y| y == 0 && x != 1).with_move_first_mine(true);
+        minefield.toggle_flag(2, 0);
+        assert_eq!(minefield.step(2, 0), StepResult::Invalid);
+        assert_eq!(minefield.step(0, 0), StepResult::Phew);
+     }
 
-/// The result of toggling a flag in the mine field
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum FlagToggleResult {
-    /// Exstng flag was removed
-    Removed,
-    /// A flag was added
-    Added,
-    /// No flag placed or removed
-    None
-}
+     #[test]
+     fn seeded_mines() {
+        let minefield = Minefield::new(16, 16).with_seeded_mines(40, 7);
+        assert_eq!(minefield.mines(), 40);
+        assert_eq!(minefield.validate(), Ok(()));
 
- #[cfg(test)]
- mod tests {
-    use super::*;
+        // Same seed, same mines
+        let same = Minefield::new(16, 16).with_seeded_mines(40, 7);
+        assert!(minefield.spots().zip(same.spots()).all(|(a, b)| a == b));
+        assert_eq!(minefield.state_hash(), same.state_hash());
+
+        // Different seed, different mines
+        let different = Minefield::new(16, 16).with_seeded_mines(40, 8);
+        assert!(minefield.spots().zip(different.spots()).any(|(a, b)| a != b));
+        assert_ne!(minefield.state_hash(), different.state_hash());
+     }
 
      #[test]
-     fn new_minefield() {
-        // Create empty test minefield:
-        //     0 1 2
-        // 0 [       ]
-        // 1 [       ]
-        // 2 [       ]
-        // 3 [       ]
-        //
-        let width = 3;
-        let height = 4;
-        let minefield = Minefield::new(width, height);
+     fn history() {
+        let mut minefield = Minefield::new(3, 3).with_seeded_mines(1, 1);
 
-        for ((x, y), spot) in &minefield.field {
-            assert_eq!(spot.state, SpotState::HiddenEmpty { neighboring_mines: 0 });
-            assert!(*x < width);
-            assert!(*y < height);
-        }
+        // Nothing is recorded before history is enabled
+        minefield.toggle_flag(0, 0);
+        assert!(minefield.history().is_empty());
+
+        minefield.enable_history();
+        minefield.toggle_flag(0, 0);
+        minefield.step(5, 5);
+        minefield.auto_step(1, 1);
+
+        let actions: Vec<Action> = minefield.history().iter().map(|timestamped| timestamped.action).collect();
+        assert_eq!(
+            actions,
+            vec![Action::ToggleFlag { x: 0, y: 0 }, Action::Step { x: 5, y: 5 }, Action::AutoStep { x: 1, y: 1 }]
+        );
+        assert!(minefield.history().windows(2).all(|pair| pair[0].elapsed <= pair[1].elapsed));
      }
 
      #[test]
-     fn place_mines() {
-         // Create empty minefield
-        let width = 3;
-        let height = 4;
-        let mut minefield = Minefield::new(width, height);
+     fn replay_verification() {
+        let (width, height, mines, seed) = (8, 8, 10, 42);
 
-        // Place Mine
-        //     0 1 2
-        // 0 [   1 ☢ ]
-        // 1 [   1 1 ]
-        // 2 [       ]
-        // 3 [       ]
-        //
-        let mine_x = 2;
-        let mine_y = 0;
-        minefield.place_mine(mine_x, mine_y);
+        // Play a winning game: flag every mine, and step on every empty spot
+        let mut minefield = Minefield::new(width, height).with_seeded_mines(mines, seed);
+        let layout: Vec<_> = minefield.spots().collect();
+        minefield.enable_history();
+        let mut live_invalid_actions = 0;
+        for ((x, y), spot) in layout {
+            let action = if spot.state == SpotState::HiddenMine {
+                Action::ToggleFlag { x, y }
+            } else {
+                Action::Step { x, y }
+            };
 
-        // Was mine placed correctly?
-        assert_eq!(minefield.field.get(&(mine_x, mine_y)).unwrap().state, SpotState::HiddenMine);
+            // Flood reveals make some of the steps redundant
+            if minefield.apply(action).changed.is_empty() {
+                live_invalid_actions += 1;
+            }
+        }
+        assert!(minefield.is_cleared());
 
-        // Were the neighbors updated correctly?
-        for (nx, ny) in minefield.neighbors_coords(mine_x, mine_y) {
-            assert_eq!(minefield.field.get(&(nx, ny)).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        let actions: Vec<Action> = minefield.history().iter().map(|timestamped| timestamped.action).collect();
+        match verify_replay(width, height, mines, seed, actions.clone()) {
+            ReplayVerdict::Completed { cleared, invalid_actions, state_hash } => {
+                assert!(cleared);
+                assert_eq!(invalid_actions, live_invalid_actions);
+                assert_eq!(state_hash, minefield.state_hash());
+            },
+            verdict => panic!("unexpected verdict {:?}", verdict),
         }
 
-        // Place another mine
+        // A replay on another seed doesn't produce the same board
+        match verify_replay(width, height, mines, seed + 1, actions) {
+            ReplayVerdict::Completed { state_hash, .. } => assert_ne!(state_hash, minefield.state_hash()),
+            ReplayVerdict::ActionAfterBoom { .. } => {},
+            verdict => panic!("unexpected verdict {:?}", verdict),
+        }
+
+        // Replays with out of bounds actions are rejected
+        let actions = [Action::ToggleFlag { x: 0, y: 0 }, Action::Step { x: 8, y: 0 }];
+        assert_eq!(verify_replay(width, height, mines, seed, actions), ReplayVerdict::OutOfBounds { action_index: 1 });
+
+        // Replays which continue after stepping on a mine are rejected
+        let minefield = Minefield::new(width, height).with_seeded_mines(mines, seed);
+        let ((x, y), _spot) = minefield.spots().find(|(_, spot)| spot.state == SpotState::HiddenMine).unwrap();
+        let actions = [Action::Step { x, y }, Action::ToggleFlag { x: 0, y: 0 }];
+        assert_eq!(
+            verify_replay(width, height, mines, seed, actions),
+            ReplayVerdict::ActionAfterBoom { action_index: 1 }
+        );
+     }
+
+     #[test]
+     fn replay_player() {
         //     0 1 2
         // 0 [   1 ☢ ]
         // 1 [   1 1 ]
         // 2 [ 1 1   ]
         // 3 [ ☢ 1   ]
-        let mine_x = 0;
-        let mine_y = 3;
-        minefield.place_mine(mine_x, mine_y);
+        //
+        // with a column of holes on the right
+        let mut minefield = Minefield::with_shape(4, 4, |x, _y| x < 3).with_move_first_mine(true);
+        for (x, y) in [(2, 0), (0, 3)] {
+            minefield.place_mine(x, y).unwrap();
+        }
 
-        // Was mine placed correctly?
-        assert_eq!(minefield.field.get(&(mine_x, mine_y)).unwrap().state, SpotState::HiddenMine);
+        let mut replay = Replay::record(&mut minefield);
+        replay.apply(Action::Step { x: 0, y: 0 });
+        replay.apply(Action::ToggleFlag { x: 2, y: 0 });
+        replay.apply(Action::Step { x: 2, y: 3 });
+        replay.apply(Action::Step { x: 0, y: 3 });
+        assert_eq!(replay.actions().len(), 4);
+        assert_eq!(replay.minefield().game_state(), GameState::Lost);
+        let (spec, actions) = replay.into_parts();
+        assert_eq!(spec.mines, vec![(2, 0), (0, 3)]);
+        assert_eq!(spec.holes.len(), 4);
 
-        // Were the neighbors updated correctly?
-        for (nx, ny) in minefield.neighbors_coords(mine_x, mine_y) {
-            assert_eq!(minefield.field.get(&(nx, ny)).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 1 });
+        // Rebuild the position after each move by playing the game live
+        let mut live = spec.build();
+        let mut positions = vec![live.clone()];
+        for &action in &actions {
+            live.apply(action);
+            positions.push(live.clone());
         }
+        assert_eq!(live, minefield);
 
-        // Place another mine
-        //     0 1 2
-        // 0 [ 1 2 ☢ ]
-        // 1 [ ☢ 2 1 ]
-        // 2 [ 2 2   ]
-        // 3 [ ☢ 1   ]
-        let mine_x = 0;
-        let mine_y = 1;
-        minefield.place_mine(mine_x, mine_y);
+        let mut player = ReplayPlayer::new(spec, actions);
+        assert_eq!(player.len(), 4);
+        assert_eq!(player.minefield(), &positions[0]);
+        assert!(!player.step_back());
 
-        // Was mine placed correctly?
-        assert_eq!(minefield.field.get(&(mine_x, mine_y)).unwrap().state, SpotState::HiddenMine);
+        assert_eq!(player.step_forward().unwrap().changed, vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)]);
+        assert_eq!(player.minefield(), &positions[1]);
+        assert_eq!(player.seek(3), &positions[3]);
+        assert!(player.step_back());
+        assert_eq!((player.position(), player.minefield()), (2, &positions[2]));
 
-        // Were the neighbors updated correctly?
-        for n_coords in minefield.neighbors_coords(mine_x,  mine_y) {
-            let expected_mine_count = if n_coords == (0, 0) { 1 } else { 2 };
-            assert_eq!(
-                minefield.field.get(&n_coords).unwrap().state, 
-                SpotState::HiddenEmpty { neighboring_mines: expected_mine_count }
-            );
-        }
+        // Seeking past the end stops at the last move
+        assert_eq!(player.seek(10), &positions[4]);
+        assert_eq!(player.position(), 4);
+        assert_eq!(player.step_forward(), None);
+        assert_eq!(player.seek(0), &positions[0]);
      }
 
      #[test]
-     fn step() {
-         // Create empty minefield
-         let width = 3;
-         let height = 4;
-         let mut minefield = Minefield::new(width, height);
+     fn replay_options() {
+        //     0 1 2 3
+        // 0 [ ☢       ]
+        // 1 [         ]
+        // 2 [         ]
+        // 3 [       ☢ ]
+        //
+        // with the first mine moved away from the first step, but not onto the reserved spot next to it
+        let mut minefield = Minefield::new(4, 4)
+            .with_move_first_mine(true)
+            .with_reserved_safe([(1, 0)])
+            .with_flood(false);
+        minefield.set_flag_mode(FlagMode::FlagsAndQuestionMarks);
+        for (x, y) in [(0, 0), (3, 3)] {
+            minefield.place_mine(x, y).unwrap();
+        }
 
-        // Place mines
+        let mut replay = Replay::record(&mut minefield);
+        replay.apply(Action::Step { x: 0, y: 0 });
+        replay.apply(Action::Step { x: 0, y: 3 });
+        replay.apply(Action::ToggleFlag { x: 3, y: 3 });
+        replay.apply(Action::ToggleFlag { x: 3, y: 3 });
+        let (spec, actions) = replay.into_parts();
+        assert_eq!(spec.reserved, vec![(1, 0)]);
+        assert!(!spec.flood_enabled);
+        assert!(minefield.spot(2, 0).unwrap().is_mine());
+        assert_eq!(minefield.spots().filter(|(_coords, spot)| spot.is_revealed()).count(), 2);
+        assert!(minefield.spot(3, 3).unwrap().is_questioned());
+
+        let mut player = ReplayPlayer::new(spec, actions);
+        assert_eq!(player.seek(4), &minefield);
+     }
+
+     #[test]
+     fn apply() {
+        // Create minefield with mines
         //     0 1 2
         // 0 [   1 ☢ ]
         // 1 [   1 1 ]
         // 2 [ 1 1   ]
         // 3 [ ☢ 1   ]
-        let mine_x = 2;
-        let mine_y = 0;
-        minefield.place_mine(mine_x, mine_y);
-        let mine_x = 0;
-        let mine_y = 3;
-        minefield.place_mine(mine_x, mine_y);
-
-        // Step on spot neighboring mine
-        let step_x = 1;
-        let step_y = 2;
-        let step_result = minefield.step(step_x, step_y);
-
-        // Step was success, and only one spot was revealed
-        //     0 1 2
-        // 0 [ • • • ]
-        // 1 [ • • • ]
-        // 2 [ • 1 • ]
-        // 3 [ • • • ]
-        assert_eq!(step_result, StepResult::Phew);
-        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Revealed);
-        // for (nx, ny) in minefield.neighbors_coords(step_x, step_y) {
-        //     assert_eq!(minefield.field[nx as usize][ny as usize].state, SpotState::Hidden);
-        // }
+        let mut minefield = Minefield::from_fn(3, 4, |x, y| [(2, 0), (0, 3)].contains(&(x, y)));
 
-        // Step on spot with no neighboring mines
-        let step_x = 0;
-        let step_y = 1;
-        let step_result = minefield.step(step_x, step_y);
+        // Flag a spot
+        let result = minefield.apply(Action::ToggleFlag { x: 1, y: 3 });
+        assert_eq!(result.outcome, ActionOutcome::Flag(FlagToggleResult::Added));
+        assert_eq!(result.changed, vec![(1, 3)]);
 
-        // Step was success, and neighbors were flood revealed
+        // Step on spot with no neighboring mines, which flood reveals its neighbors
         //     0 1 2
         // 0 [   1 • ]
         // 1 [   1 • ]
         // 2 [ 1 1 • ]
-        // 3 [ • • • ]
-        assert_eq!(step_result, StepResult::Phew);
-        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Revealed);
-        // for (nx, ny) in minefield.neighbors_coords(step_x, step_y) {
-        //     assert_eq!(minefield.field[nx as usize][ny as usize].state, SpotState::Revealed);
-        // }
+        // 3 [ • ⚐ • ]
+        let result = minefield.apply(Action::Step { x: 0, y: 0 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Phew));
+        let mut changed = result.changed;
+        changed.sort();
+        assert_eq!(changed, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
 
-        // Step on mine
-        let step_x = 2;
-        let step_y = 0;
-        let step_result = minefield.step(step_x, step_y);
+        // Invalid actions change nothing
+        let result = minefield.apply(Action::Step { x: 1, y: 1 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Invalid));
+        assert!(result.changed.is_empty());
+        let result = minefield.apply(Action::ToggleFlag { x: 3, y: 0 });
+        assert_eq!(result.outcome, ActionOutcome::Flag(FlagToggleResult::None));
+        assert!(result.changed.is_empty());
 
-        // Step was Boom, and only mine spot was newly revealed
+        // Auto step around spots without enough flags
+        let result = minefield.apply(Action::AutoStep { x: 1, y: 0 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Invalid));
+        assert!(result.changed.is_empty());
+        let result = minefield.apply(Action::AutoStep { x: 1, y: 1 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Invalid));
+
+        // Auto step around a spot after flagging its mine, which flood reveals around the flag at (1, 3)
+        //     0 1 2
+        // 0 [   1 ⚐ ]
+        // 1 [   1 1 ]
+        // 2 [ 1 1   ]
+        // 3 [ • ⚐   ]
+        minefield.toggle_flag(2, 0);
+        let result = minefield.apply(Action::AutoStep { x: 1, y: 1 });
+        assert_eq!(result.outcome, ActionOutcome::Step(StepResult::Phew));
+        let mut changed = result.changed;
+        changed.sort();
+        assert_eq!(changed, vec![(2, 1), (2, 2), (2, 3)]);
+     }
+
+     #[test]
+     fn neighbors() {
+        // Create minefield with a mine
         //     0 1 2
         // 0 [   1 ☢ ]
-        // 1 [   1 • ]
-        // 2 [ 1 1 • ]
-        // 3 [ • • • ]
-        assert_eq!(step_result, StepResult::Boom);
-        // assert_eq!(minefield.field[step_x as usize][step_y as usize].state, SpotState::Exploded);
-        // for (x, y) in minefield.neighbors_coords(step_x,  step_y) {
-        //     let expected_spot_state= if (x, y) == (2, 1) { SpotState::Hidden } else { SpotState::Revealed };
-        //     assert_eq!(minefield.field[x as usize][y as usize].state, expected_spot_state);
-        // }
+        // 1 [   1 1 ]
+        // 2 [       ]
+        let mut minefield = Minefield::new(3, 3);
+        minefield.place_mine(2, 0).unwrap();
+
+        // Center spot has 8 neighbors
+        let neighbors: Vec<_> = minefield.neighbors(1, 1).collect();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.contains(&((2, 0), Spot { state: SpotState::HiddenMine })));
+        assert!(neighbors.contains(&((0, 0), Spot { state: SpotState::HiddenEmpty { neighboring_mines: 0 } })));
+        assert!(!neighbors.iter().any(|(coords, _spot)| *coords == (1, 1)));
+
+        // Corner spot has 3 neighbors
+        let neighbors: Vec<_> = minefield.neighbors(0, 2).collect();
+        assert_eq!(neighbors.len(), 3);
+        for ((x, y), spot) in neighbors {
+            assert_eq!(spot.state, minefield.spot(x, y).unwrap().state);
+        }
+
+        // Coordinates outside the field have no neighbors within it
+        assert_eq!(minefield.neighbors(5, 5).count(), 0);
      }
 
      #[test]
-     fn flood_reveal() {
-        // Create empty bigger minefield
-        //     0 1 2 3 4 5 6 7 8 9
-        // 0 [     1 ☢ 1           ]
-        // 1 [     1 1 1           ]
-        // 2 [           1 1 1     ]
-        // 3 [   1 1 1   1 ☢ 1 1 1 ]
-        // 4 [   1 ☢ 1   1 1 1 1 ☢ ]
-        // 5 [   1 1 1         1 1 ]
-        // 6 [         1 1 2 1 1   ]
-        // 7 [         1 ☢ 2 ☢ 1   ]
-        // 8 [         1 1 2 1 1   ]
-        // 9 [                     ]
-        let width = 10;
-        let height = 10;
-        let mut minefield = Minefield::new(width, height);
+     fn shaped_field() {
+        // Create a field split in two by a column of holes
+        //     0 1 2 3 4
+        // 0 [   1 ▒     ]
+        // 1 [ ☢ 1 ▒     ]
+        // 2 [ 1 1 ▒     ]
+        let mut minefield = Minefield::with_shape(5, 3, |x, _y| x != 2);
+        minefield.place_mine(0, 1).unwrap();
 
-        let mine_coords = [(2, 4), (5, 7), (7, 7), (9, 4), (6, 3), (3, 0)];
-        for (x, y) in mine_coords {
-            minefield.place_mine(x, y);
+        assert_eq!(minefield.width(), 5);
+        assert_eq!(minefield.height(), 3);
+        assert_eq!(minefield.cell_count(), 12);
+        assert_eq!(minefield.spots().count(), 12);
+
+        // Holes have no spots, and are nobody's neighbors
+        assert!(minefield.spot(2, 1).is_none());
+        assert_eq!(minefield.neighbors_coords(1, 1).count(), 5);
+        assert_eq!(minefield.neighbors_coords(3, 1).count(), 5);
+        assert_eq!(minefield.step(2, 1), StepResult::Invalid);
+        assert_eq!(minefield.toggle_flag(2, 1), FlagToggleResult::None);
+
+        // Flood reveal treats holes as walls
+        //     0 1 2 3 4
+        // 0 [ • • ▒     ]
+        // 1 [ • • ▒     ]
+        // 2 [ • • ▒     ]
+        assert_eq!(minefield.step(4, 0), StepResult::Phew);
+        for y in 0..3 {
+            for x in 0..2 {
+                assert!(minefield.spot(x, y).unwrap().is_hidden());
+            }
+            for x in 3..5 {
+                assert_eq!(minefield.spot(x, y).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 0 });
+            }
         }
 
-        // Place a flag
-        //     0 1 2 3 4 5 6 7 8 9
-        // 0 [ • • • • • • • • • • ]
-        // 1 [ • • • • • ⚐ • • • • ]
-        // 2 [ • • • • • • • • • • ]
-        // 3 [ • • • • • • • • • • ]
-        // 4 [ • • • • • • • • • • ]
-        // 5 [ • • • • • • • • • • ]
-        // 6 [ • • • • • • • • • • ]
-        // 7 [ • • • • • • • • • • ]
-        // 8 [ • • • • • • • • • • ]
-        // 9 [ • • • • • • • • • • ]
-        let flag_x = 5;
-        let flag_y = 1;
-        let toggle_result = minefield.toggle_flag(flag_x, flag_y);
-        assert_eq!(toggle_result, FlagToggleResult::Added);
+        // Holes don't need to be cleared
+        for (x, y) in [(0, 0), (1, 0), (1, 1), (0, 2), (1, 2)] {
+            minefield.step(x, y);
+        }
+        minefield.toggle_flag(0, 1);
+        assert!(minefield.is_cleared());
+     }
 
-        // Step on spot (x=9, y=6)
-        //     0 1 2 3 4 5 6 7 8 9
-        // 0 [     1 • • • • • • • ]
-        // 1 [     1 1 1 ⚐ • • • • ]
-        // 2 [           1 • • • • ]
-        // 3 [   1 1 1   1 • • • • ]
-        // 4 [   1 • 1   1 1 1 1 • ]
-        // 5 [   1 1 1         1 1 ]
-        // 6 [         1 1 2 1 1   ]
-        // 7 [         1 • • • 1   ]
-        // 8 [         1 1 2 1 1   ]
-        // 9 [                     ]
-        let step_x = 9;
-        let step_y = 6;
-        let step_result = minefield.step(step_x, step_y);
-        assert_eq!(step_result, StepResult::Phew);
+     #[test]
+     fn shaped_field_mines() {
+        // Create a donut shaped field, and fill it with mines
+        let minefield = Minefield::with_shape(5, 5, |x, y| !(x == 2 && y == 2)).with_mines(100);
 
-        // All mines are still hidden
-        for n_coords in mine_coords {
-            assert_eq!(minefield.field.get(&n_coords).unwrap().state, SpotState::HiddenMine);
+        assert_eq!(minefield.cell_count(), 24);
+        assert_eq!(minefield.mines(), 24);
+        for (_spot_coords, spot) in minefield.spots() {
+            assert_eq!(spot.state, SpotState::HiddenMine);
         }
+     }
 
-        // Flood revealed half maze
-        assert_eq!(minefield.field.get(&(7, 5)).unwrap().state, SpotState::RevealedEmpty { neighboring_mines: 0 });
+     #[test]
+     fn spot_predicates() {
+        // Expected `(is_mine, is_flagged, is_revealed, is_hidden, is_exploded, is_resolved, neighboring_mines)` of each
+        // state. The match is exhaustive, so that new states have to be added here.
+        let expected = |state: SpotState| match state {
+            SpotState::HiddenEmpty { neighboring_mines: n } => (false, false, false, true, false, false, Some(n)),
+            SpotState::HiddenMine => (true, false, false, true, false, false, None),
+            SpotState::FlaggedEmpty { neighboring_mines: n } => (false, true, false, false, false, false, Some(n)),
+            SpotState::FlaggedMine => (true, true, false, false, false, true, None),
+            SpotState::RevealedEmpty { neighboring_mines: n } => (false, false, true, false, false, true, Some(n)),
+            SpotState::ExplodedMine => (true, false, true, false, true, false, None),
+            SpotState::WrongFlag { neighboring_mines: n } => (false, true, false, false, false, false, Some(n)),
+            SpotState::RevealedMine => (true, false, true, false, false, false, None),
+            SpotState::QuestionedEmpty { neighboring_mines: n } => (false, false, false, true, false, false, Some(n)),
+            SpotState::QuestionedMine => (true, false, false, true, false, false, None),
+        };
 
-        // Flag is still there
-        assert_eq!(
-            minefield.field.get(&(flag_x, flag_y)).unwrap().state, 
-            SpotState::FlaggedEmpty { neighboring_mines: 0 }
-        );
+        let states = [
+            SpotState::HiddenEmpty { neighboring_mines: 3 },
+            SpotState::HiddenMine,
+            SpotState::FlaggedEmpty { neighboring_mines: 0 },
+            SpotState::FlaggedMine,
+            SpotState::RevealedEmpty { neighboring_mines: 8 },
+            SpotState::ExplodedMine,
+            SpotState::WrongFlag { neighboring_mines: 2 },
+            SpotState::RevealedMine,
+            SpotState::QuestionedEmpty { neighboring_mines: 5 },
+            SpotState::QuestionedMine,
+        ];
 
-        // Insulated portion of field is still hidden
-        assert_eq!(minefield.field.get(&(9, 0)).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
-        assert_eq!(minefield.field.get(&(7, 1)).unwrap().state, SpotState::HiddenEmpty { neighboring_mines: 0 });
+        for state in states {
+            let spot = Spot { state };
+            assert_eq!(spot.state(), state);
+            assert_eq!(
+                (
+                    spot.is_mine(),
+                    spot.is_flagged(),
+                    spot.is_revealed(),
+                    spot.is_hidden(),
+                    spot.is_exploded(),
+                    spot.is_resolved(),
+                    spot.neighboring_mines(),
+                ),
+                expected(state),
+                "{:?}",
+                state
+            );
+        }
+     }
+
+     #[test]
+     fn packed_spot_round_trip() {
+        // Every possible spot state, with every possible count of neighboring mines
+        let states = (0..=8).flat_map(|neighboring_mines| {
+            [
+                SpotState::HiddenEmpty { neighboring_mines },
+                SpotState::HiddenMine,
+                SpotState::FlaggedEmpty { neighboring_mines },
+                SpotState::FlaggedMine,
+                SpotState::RevealedEmpty { neighboring_mines },
+                SpotState::ExplodedMine,
+                SpotState::WrongFlag { neighboring_mines },
+                SpotState::RevealedMine,
+                SpotState::QuestionedEmpty { neighboring_mines },
+                SpotState::QuestionedMine,
+            ]
+        });
+
+        for state in states {
+            let packed = PackedSpot::from(Spot { state });
+            assert_eq!(Spot::from(packed).state, state);
+
+            // Spots are never mistaken for holes
+            assert_ne!(packed, PackedSpot::HOLE);
+            assert!(packed.is_valid());
+        }
+
+        // Distinct states never share the same packed representation
+        let hidden_empty = PackedSpot::from(Spot { state: SpotState::HiddenEmpty { neighboring_mines: 0 } });
+        let hidden_mine = PackedSpot::from(Spot { state: SpotState::HiddenMine });
+        let exploded_mine = PackedSpot::from(Spot { state: SpotState::ExplodedMine });
+        assert_ne!(hidden_empty, hidden_mine);
+        assert_ne!(hidden_mine, exploded_mine);
+     }
+
+     #[test]
+     fn packed_field_layout() {
+        // The field buffer holds exactly one byte per spot, in row-major order
+        let mut minefield = Minefield::new(3, 4);
+        assert_eq!(minefield.field.len(), 12);
+        assert_eq!(std::mem::size_of::<PackedSpot>(), 1);
+
+        minefield.place_mine(2, 1).unwrap();
+        assert_eq!(Spot::from(minefield.field[5]).state, SpotState::HiddenMine);
+        assert_eq!(minefield.spot(2, 1).unwrap().state, SpotState::HiddenMine);
+
+        // Coordinates outside the field grid have no spot
+        assert!(minefield.spot(3, 0).is_none());
+        assert!(minefield.spot(0, 4).is_none());
      }
 
      #[allow(dead_code)]
@@ -620,15 +9160,19 @@ pub enum FlagToggleResult {
             // Y Axis
             print!("{:?} [", y);
             for x in 0..minefield.width {
-                match minefield.field.get(&(x, y)).unwrap().state {
+                match minefield.spot(x, y).unwrap().state {
                     SpotState::FlaggedMine | 
                     SpotState::HiddenMine | 
-                    SpotState::ExplodedMine => {
+                    SpotState::ExplodedMine |
+                    SpotState::RevealedMine |
+                    SpotState::QuestionedMine => {
                         print!(" ☢");
                     },
                     SpotState::FlaggedEmpty { neighboring_mines } | 
                     SpotState::HiddenEmpty { neighboring_mines } | 
-                    SpotState::RevealedEmpty { neighboring_mines } => {
+                    SpotState::RevealedEmpty { neighboring_mines } |
+                    SpotState::WrongFlag { neighboring_mines } |
+                    SpotState::QuestionedEmpty { neighboring_mines } => {
                         if neighboring_mines > 0 {
                             print!(" {}", neighboring_mines);
                         } else {
@@ -655,7 +9199,7 @@ pub enum FlagToggleResult {
             // Y Axis
             print!("{:?} [", y);
             for x in 0..minefield.width {
-                match minefield.field.get(&(x, y)).unwrap().state {
+                match minefield.spot(x, y).unwrap().state {
                     SpotState::HiddenEmpty { neighboring_mines: _ } => {
                         print!(" •");
                     },
@@ -678,6 +9222,15 @@ pub enum FlagToggleResult {
                     SpotState::ExplodedMine => {
                         print!(" 💥");
                     },
+                    SpotState::WrongFlag { neighboring_mines: _ } => {
+                        print!(" ⨯");
+                    },
+                    SpotState::RevealedMine => {
+                        print!(" ☢");
+                    },
+                    SpotState::QuestionedEmpty { neighboring_mines: _ } | SpotState::QuestionedMine => {
+                        print!(" ?");
+                    },
                 }
             }
             println!(" ]");